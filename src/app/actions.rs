@@ -1,58 +1,305 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
 use crossterm::event::{KeyEvent, KeyCode, KeyModifiers};
 use strum::IntoEnumIterator;
-use strum_macros::{Display as StrumDisplay, EnumIter};
+use strum_macros::{Display as StrumDisplay, EnumIter, EnumString};
+
+use crate::utils::key_display::{KeySequenceWrapper, parse_key_event, parse_key_sequence};
+
+/// Keys to press one after the other to trigger an action, most of them being a single key
+pub type KeySequence = Vec<KeyEvent>;
+
+/// Default leader key, standing for `<leader>` in the configured key sequences
+pub const DEFAULT_LEADER: &str = "\\";
 
-use crate::utils::key_display::KeyEventWrapper;
+fn key(code: KeyCode, modifiers: KeyModifiers) -> KeySequence {
+    vec![KeyEvent::new(code, modifiers)]
+}
 
 /// We define all available action
-#[derive(Debug, Clone, Copy, Eq, PartialEq, StrumDisplay, EnumIter)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, StrumDisplay, EnumIter, EnumString)]
 pub enum Action {
     Quit,
+    Suspend,
     ToggleCurrent,
     DeleteSelectedEntries,
+    Undo,
+    ShowDeletions,
     Up,
     Down,
     EditPath,
     EditFilter,
     UnfocusTextArea,
+    CompletePath,
+    PreviousHistoryEntry,
+    NextHistoryEntry,
+    SelectAll,
+    SelectNone,
+    InvertSelection,
+    SelectRangeUp,
+    SelectRangeDown,
+    SelectMatching,
+    Search,
+    SaveSearch,
+    OpenSearchPicker,
+    LoadSearch,
+    DeleteSearch,
+    ClosePicker,
+    NextMatch,
+    PreviousMatch,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    First,
+    Last,
+    ToggleTreeView,
+    ToggleAbsolutePaths,
+    CollapseNode,
+    ExpandNode,
+    TogglePreview,
+    ToggleLogs,
+    ToggleBrowseMode,
+    EnterDirectory,
+    ParentDirectory,
 }
 
 impl Action {
-    /// List of key associated to action
-    pub fn keys(&self) -> Vec<KeyEvent> {
+    /// List of key sequences associated to action when not overridden by the configuration
+    pub fn default_keys(&self) -> Vec<KeySequence> {
+        match self {
+            Action::Quit => vec![key(KeyCode::Char('c'), KeyModifiers::CONTROL), key(KeyCode::Char('q'), KeyModifiers::NONE)],
+            Action::Suspend => vec![key(KeyCode::Char('z'), KeyModifiers::CONTROL)],
+            Action::ToggleCurrent => vec![key(KeyCode::Char(' '), KeyModifiers::NONE)],
+            Action::DeleteSelectedEntries => vec![[key(KeyCode::Char('d'), KeyModifiers::NONE), key(KeyCode::Char('d'), KeyModifiers::NONE)].concat()],
+            Action::Undo => vec![key(KeyCode::Char('u'), KeyModifiers::NONE)],
+            Action::ShowDeletions => vec![key(KeyCode::Char('U'), KeyModifiers::SHIFT)],
+            Action::Up => vec![key(KeyCode::Up, KeyModifiers::NONE), key(KeyCode::Char('k'), KeyModifiers::NONE)],
+            Action::Down => vec![key(KeyCode::Down, KeyModifiers::NONE), key(KeyCode::Char('j'), KeyModifiers::NONE)],
+            Action::EditPath => vec![key(KeyCode::Char('p'), KeyModifiers::NONE)],
+            Action::EditFilter => vec![key(KeyCode::Char('f'), KeyModifiers::NONE)],
+            Action::UnfocusTextArea => vec![key(KeyCode::Esc, KeyModifiers::NONE)],
+            Action::CompletePath => vec![key(KeyCode::Tab, KeyModifiers::NONE)],
+            Action::PreviousHistoryEntry => vec![key(KeyCode::Up, KeyModifiers::NONE)],
+            Action::NextHistoryEntry => vec![key(KeyCode::Down, KeyModifiers::NONE)],
+            Action::SelectAll => vec![key(KeyCode::Char('a'), KeyModifiers::NONE)],
+            Action::SelectNone => vec![key(KeyCode::Char('A'), KeyModifiers::SHIFT)],
+            Action::InvertSelection => vec![key(KeyCode::Char('i'), KeyModifiers::NONE)],
+            Action::SelectRangeUp => vec![key(KeyCode::Up, KeyModifiers::SHIFT)],
+            Action::SelectRangeDown => vec![key(KeyCode::Down, KeyModifiers::SHIFT)],
+            Action::SelectMatching => vec![key(KeyCode::Char('s'), KeyModifiers::NONE)],
+            Action::Search => vec![key(KeyCode::Char('/'), KeyModifiers::NONE)],
+            Action::SaveSearch => vec![key(KeyCode::Char('S'), KeyModifiers::SHIFT)],
+            Action::OpenSearchPicker => vec![key(KeyCode::Char('o'), KeyModifiers::NONE)],
+            Action::LoadSearch => vec![key(KeyCode::Enter, KeyModifiers::NONE)],
+            Action::DeleteSearch => vec![key(KeyCode::Delete, KeyModifiers::NONE)],
+            Action::ClosePicker => vec![key(KeyCode::Esc, KeyModifiers::NONE)],
+            Action::NextMatch => vec![key(KeyCode::Char('n'), KeyModifiers::NONE)],
+            Action::PreviousMatch => vec![key(KeyCode::Char('N'), KeyModifiers::SHIFT)],
+            Action::PageUp => vec![key(KeyCode::PageUp, KeyModifiers::NONE)],
+            Action::PageDown => vec![key(KeyCode::PageDown, KeyModifiers::NONE)],
+            Action::HalfPageUp => vec![key(KeyCode::Char('u'), KeyModifiers::CONTROL)],
+            Action::HalfPageDown => vec![key(KeyCode::Char('d'), KeyModifiers::CONTROL)],
+            Action::First => vec![key(KeyCode::Home, KeyModifiers::NONE), [key(KeyCode::Char('g'), KeyModifiers::NONE), key(KeyCode::Char('g'), KeyModifiers::NONE)].concat()],
+            Action::Last => vec![key(KeyCode::End, KeyModifiers::NONE), key(KeyCode::Char('G'), KeyModifiers::SHIFT)],
+            Action::ToggleTreeView => vec![key(KeyCode::Char('t'), KeyModifiers::NONE)],
+            Action::ToggleAbsolutePaths => vec![key(KeyCode::Char('P'), KeyModifiers::SHIFT)],
+            Action::CollapseNode => vec![key(KeyCode::Left, KeyModifiers::NONE), key(KeyCode::Char('h'), KeyModifiers::NONE)],
+            Action::ExpandNode => vec![key(KeyCode::Right, KeyModifiers::NONE), key(KeyCode::Char('l'), KeyModifiers::NONE)],
+            Action::TogglePreview => vec![key(KeyCode::Char('v'), KeyModifiers::NONE)],
+            Action::ToggleLogs => vec![key(KeyCode::Char('L'), KeyModifiers::SHIFT)],
+            Action::ToggleBrowseMode => vec![key(KeyCode::Char('b'), KeyModifiers::NONE)],
+            Action::EnterDirectory => vec![key(KeyCode::Enter, KeyModifiers::NONE), key(KeyCode::Right, KeyModifiers::NONE), key(KeyCode::Char('l'), KeyModifiers::NONE)],
+            Action::ParentDirectory => vec![key(KeyCode::Backspace, KeyModifiers::NONE), key(KeyCode::Left, KeyModifiers::NONE), key(KeyCode::Char('h'), KeyModifiers::NONE)],
+        }
+    }
+}
+
+/// What currently receives the keys, each context having its own set of actions
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, StrumDisplay, EnumIter)]
+pub enum ActionContext {
+    /// Flat list of the matched entries
+    List,
+    /// Tree view of the matched entries
+    Tree,
+    /// Disk usage browser
+    Browse,
+    EditPath,
+    EditFilter,
+    /// Prompt of the pattern selecting entries
+    SelectPattern,
+    Search,
+    /// Prompt of the name of the saved search
+    SaveSearch,
+    /// List of the saved searches
+    SearchPicker,
+    /// List of the recent deletions
+    Deletions,
+}
+
+impl ActionContext {
+    /// Actions available in this context
+    pub fn actions(&self) -> Vec<Action> {
+        // shared by every list
+        let list_actions = [
+            Action::Quit,
+            Action::Suspend,
+            Action::DeleteSelectedEntries,
+            Action::Undo,
+            Action::ShowDeletions,
+            Action::ToggleCurrent,
+            Action::Up,
+            Action::Down,
+            Action::PageUp,
+            Action::PageDown,
+            Action::HalfPageUp,
+            Action::HalfPageDown,
+            Action::First,
+            Action::Last,
+            Action::SelectAll,
+            Action::SelectNone,
+            Action::InvertSelection,
+            Action::SelectRangeUp,
+            Action::SelectRangeDown,
+            Action::TogglePreview,
+            Action::ToggleLogs,
+            Action::ToggleBrowseMode,
+        ];
+        // shared by the views of the matched entries
+        let filter_actions = [
+            Action::EditPath,
+            Action::EditFilter,
+            Action::SelectMatching,
+            Action::Search,
+            Action::NextMatch,
+            Action::PreviousMatch,
+            Action::ToggleTreeView,
+            Action::ToggleAbsolutePaths,
+            Action::SaveSearch,
+            Action::OpenSearchPicker,
+        ];
+
         match self {
-            Action::Quit => vec![KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL), KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)],
-            Action::ToggleCurrent => vec![KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)],
-            Action::DeleteSelectedEntries => vec![KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)],
-            Action::Up => vec![KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)],
-            Action::Down => vec![KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)],
-            Action::EditPath => vec![KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)],
-            Action::EditFilter => vec![KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE)],
-            Action::UnfocusTextArea => vec![KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)],
+            ActionContext::List => [list_actions.as_slice(), &filter_actions].concat(),
+            ActionContext::Tree => [list_actions.as_slice(), &filter_actions, &[Action::CollapseNode, Action::ExpandNode]].concat(),
+            ActionContext::Browse => [list_actions.as_slice(), &[Action::EnterDirectory, Action::ParentDirectory]].concat(),
+            ActionContext::EditPath => vec![Action::UnfocusTextArea, Action::CompletePath, Action::PreviousHistoryEntry, Action::NextHistoryEntry],
+            ActionContext::EditFilter => vec![Action::UnfocusTextArea, Action::PreviousHistoryEntry, Action::NextHistoryEntry],
+            ActionContext::SelectPattern
+            | ActionContext::Search
+            | ActionContext::SaveSearch => vec![Action::UnfocusTextArea],
+            ActionContext::SearchPicker => vec![Action::Up, Action::Down, Action::LoadSearch, Action::DeleteSearch, Action::ClosePicker],
+            ActionContext::Deletions => vec![Action::Up, Action::Down, Action::Undo, Action::ClosePicker],
         }
     }
+
+    /// Keys not bound to an action are typed in a text area
+    pub fn is_text_input(&self) -> bool {
+        matches!(self, ActionContext::EditPath | ActionContext::EditFilter | ActionContext::SelectPattern | ActionContext::Search | ActionContext::SaveSearch)
+    }
+}
+
+/// Keys associated to each action, defaults overridden by the configuration file
+#[derive(Debug, Clone)]
+pub struct KeyBindings(HashMap<Action, Vec<KeySequence>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(Action::iter().map(|action| (action, action.default_keys())).collect())
+    }
+}
+
+impl KeyBindings {
+    /// Override the default bindings with a map of action names to key strings,
+    /// written the way they are displayed in the help (e.g. `ctrl+c`, `shift+A`, `PageUp`, `g g`, `<leader> d`)
+    pub fn from_config(keys: &HashMap<String, Vec<String>>, leader: &str) -> Result<Self, String> {
+        let mut bindings = Self::default();
+        let mut errors = vec![];
+
+        let leader = parse_key_event(leader).map_err(|e| format!("Invalid leader key: {}", e))?;
+
+        for (action_name, key_strings) in keys {
+            let action = match Action::from_str(action_name) {
+                Ok(action) => action,
+                Err(_) => {
+                    errors.push(format!("Unknown action {}", action_name));
+                    continue;
+                }
+            };
+
+            let parsed_keys = key_strings
+                .iter()
+                .map(|key_string| parse_key_sequence(key_string, &leader).map_err(|e| format!("Invalid key for action {}: {}", action_name, e)))
+                .collect::<Result<Vec<_>, _>>();
+
+            match parsed_keys {
+                Ok(parsed_keys) => { bindings.0.insert(action, parsed_keys); },
+                Err(e) => errors.push(e),
+            }
+        }
+
+        errors
+            .is_empty()
+            .then_some(bindings)
+            .ok_or_else(|| errors.join("; "))
+    }
+
+    pub fn keys(&self, action: &Action) -> &[KeySequence] {
+        self.0.get(action).map_or(&[], |keys| keys.as_slice())
+    }
+}
+
+/// Result of looking up the keys pressed so far
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum KeyMatch {
+    Action(Action),
+    /// The keys are the beginning of a longer sequence
+    Pending,
+    None,
 }
 
 /// The application should have some contextual actions.
 #[derive(Default, Debug, Clone)]
-pub struct Actions(Vec<Action>);
+pub struct Actions {
+    actions: Vec<Action>,
+    bindings: KeyBindings,
+}
 
 impl Actions {
-    /// Given a key, find the corresponding action
-    pub fn find(&self, key: KeyEvent) -> Option<Action> {
-        Action::iter()
-            .filter(|action| self.0.contains(action))
-            .find(|action| action.keys().contains(&key))
+    /// Create a set of actions, failing if two of them share a key
+    pub fn new(actions: impl IntoIterator<Item = Action>, bindings: KeyBindings) -> Result<Self, String> {
+        let actions = actions.into_iter().collect::<Vec<_>>();
+        check_action_conflicts(&actions, &bindings)
+            .map_err(|e| format!("Error while creating actions: {}", e))?;
+        Ok(Self { actions, bindings })
+    }
+
+    /// Given the keys pressed since the last action, find the corresponding action
+    pub fn find(&self, keys: &[KeyEvent]) -> KeyMatch {
+        let actions = || Action::iter().filter(|action| self.actions.contains(action));
+
+        if let Some(action) = actions().find(|action| self.keys(action).iter().any(|sequence| sequence == keys)) {
+            return KeyMatch::Action(action);
+        }
+        if actions().any(|action| self.keys(&action).iter().any(|sequence| sequence.starts_with(keys))) {
+            return KeyMatch::Pending;
+        }
+        KeyMatch::None
+    }
+
+    pub fn keys(&self, action: &Action) -> &[KeySequence] {
+        self.bindings.keys(action)
     }
 
     pub fn slice(&self) -> &[Action] {
-        self.0.as_slice()
+        self.actions.as_slice()
     }
 }
 
 impl From<Vec<Action>> for Actions {
     fn from(actions: Vec<Action>) -> Self {
-        Self(actions)
+        Self { actions, bindings: KeyBindings::default() }
     }
 }
 
@@ -68,39 +315,104 @@ fn vec_to_string<T: std::fmt::Display>(vec: &[T], separator: &'static str) -> St
         .unwrap()
 }
 
-fn check_action_conflicts(actions: &[Action]) -> Result<(), String> {
-    let mut map = std::collections::HashMap::new();
+fn check_action_conflicts(actions: &[Action], bindings: &KeyBindings) -> Result<(), String> {
+    let mut map = HashMap::new();
 
     for action in actions {
-        for key in action.keys().iter() {
-            map.entry(*key).or_insert_with(Vec::new).push(*action);
+        for sequence in bindings.keys(action).iter() {
+            map.entry(sequence.as_slice()).or_insert_with(Vec::new).push(*action);
         }
     }
 
-    let errors = map
+    let mut errors = map
         .iter()
         .filter(|(_, actions)| actions.len() > 1) // at least two actions share same shortcut
-        .map(|(key, actions)| {
+        .map(|(sequence, actions)| {
             format!(
                 "Conflict key {} with actions {}",
-                KeyEventWrapper(key),
+                KeySequenceWrapper(sequence),
                 vec_to_string(actions, ", ")
             )
         })
         .collect::<Vec<_>>();
 
+    // the longer sequence could never be completed
+    for (sequence, actions) in map.iter() {
+        for (prefix, prefix_actions) in map.iter() {
+            if prefix.len() < sequence.len() && sequence.starts_with(prefix) {
+                errors.push(format!(
+                    "Key {} of {} is the beginning of {} of {}",
+                    KeySequenceWrapper(prefix),
+                    vec_to_string(prefix_actions, ", "),
+                    KeySequenceWrapper(sequence),
+                    vec_to_string(actions, ", ")
+                ));
+            }
+        }
+    }
+    errors.sort();
+
     errors
         .is_empty()
         .then_some(())
         .ok_or_else(|| errors.join("; "))
 }
 
-impl FromIterator<Action> for Actions {
-    fn from_iter<T: IntoIterator<Item = Action>>(iter: T) -> Self {
-        let actions = iter.into_iter().collect::<Vec<_>>();
-        check_action_conflicts(&actions)
-            .map_err(|e|  format!("Error while creating actions: {}", e))
-            .unwrap();
-        Self(actions)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(keys: &[(&str, &str)]) -> KeyBindings {
+        let keys = keys.iter().map(|(action, key)| (action.to_string(), vec![key.to_string()])).collect();
+        KeyBindings::from_config(&keys, DEFAULT_LEADER).unwrap()
+    }
+
+    fn keys(s: &str) -> KeySequence {
+        parse_key_sequence(s, &parse_key_event(DEFAULT_LEADER).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn keys_are_matched_to_sequences() {
+        let actions = Actions::new([Action::Quit, Action::DeleteSelectedEntries, Action::First], bindings(&[("First", "<leader> g")])).unwrap();
+
+        assert_eq!(actions.find(&keys("q")), KeyMatch::Action(Action::Quit));
+        assert_eq!(actions.find(&keys("d")), KeyMatch::Pending);
+        assert_eq!(actions.find(&keys("d d")), KeyMatch::Action(Action::DeleteSelectedEntries));
+        assert_eq!(actions.find(&keys("d q")), KeyMatch::None);
+        assert_eq!(actions.find(&keys("\\")), KeyMatch::Pending);
+        assert_eq!(actions.find(&keys("<leader> g")), KeyMatch::Action(Action::First));
+        assert_eq!(actions.find(&keys("g g")), KeyMatch::None);
+        // only the actions of the context are matched
+        assert_eq!(actions.find(&keys("j")), KeyMatch::None);
+    }
+
+    #[test]
+    fn sequence_starting_with_another_key_is_a_conflict() {
+        let bindings = bindings(&[("Quit", "q"), ("DeleteSelectedEntries", "q d")]);
+
+        assert_eq!(
+            check_action_conflicts(&[Action::Quit, Action::DeleteSelectedEntries], &bindings),
+            Err(String::from("Key q of Quit is the beginning of q d of DeleteSelectedEntries"))
+        );
+        assert_eq!(check_action_conflicts(&[Action::DeleteSelectedEntries], &bindings), Ok(()));
+    }
+
+    #[test]
+    fn shared_key_is_a_conflict() {
+        let bindings = bindings(&[("Quit", "x"), ("Undo", "x")]);
+
+        assert_eq!(
+            check_action_conflicts(&[Action::Quit, Action::Undo], &bindings),
+            Err(String::from("Conflict key x with actions Quit, Undo"))
+        );
+    }
+
+    #[test]
+    fn unknown_actions_and_keys_are_errors() {
+        let keys = HashMap::from([(String::from("Explode"), vec![String::from("x")])]);
+        assert_eq!(KeyBindings::from_config(&keys, DEFAULT_LEADER).unwrap_err(), "Unknown action Explode");
+
+        let keys = HashMap::from([(String::from("Quit"), vec![String::from("ctrl+nope")])]);
+        assert_eq!(KeyBindings::from_config(&keys, DEFAULT_LEADER).unwrap_err(), "Invalid key for action Quit: unknown key nope");
     }
 }
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use walkdir::WalkDir;
+
+use crate::utils::{statefull_list::StatefulList, walker::{measure_content, DeletionTarget, EntryContent}};
+
+/// Children of a directory with their content, sorted by decreasing size
+type MeasuredChildren = Vec<(walkdir::DirEntry, EntryContent)>;
+
+/// Children measured on a worker thread, so that walking large directories does not block the interface
+#[derive(Clone, Debug)]
+pub struct BrowseTask {
+    pub dir: PathBuf,
+    result: Arc<Mutex<Option<MeasuredChildren>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl BrowseTask {
+    pub fn spawn(dir: PathBuf) -> Self {
+        let task = Self { dir, result: Arc::default(), cancelled: Arc::default() };
+
+        let (dir, result, cancelled) = (task.dir.clone(), task.result.clone(), task.cancelled.clone());
+        std::thread::spawn(move || {
+            if let Some(children) = measure_children(&dir, &cancelled) {
+                *result.lock().unwrap_or_else(|e| e.into_inner()) = Some(children);
+            }
+        });
+
+        task
+    }
+
+    /// The children once measured
+    pub fn take_result(&self) -> Option<MeasuredChildren> {
+        self.result.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Stop measuring, the directory is no longer displayed
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Measure each child of `dir`, `None` if `cancelled` is set meanwhile
+fn measure_children(dir: &Path, cancelled: &AtomicBool) -> Option<MeasuredChildren> {
+    let mut children = vec![];
+    for entry in WalkDir::new(dir).min_depth(1).max_depth(1).into_iter().filter_map(|e| e.ok()) {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let content = measure_content(entry.path());
+        children.push((entry, content));
+    }
+    children.sort_by_key(|(_, content)| std::cmp::Reverse(content.size));
+    Some(children)
+}
+
+/// State of the disk usage browser, listing the children of a directory by size
+#[derive(Clone, Default)]
+pub struct BrowseState {
+    /// Directory the browser cannot go above
+    pub root: PathBuf,
+    pub current_dir: PathBuf,
+    pub entries: StatefulList<walkdir::DirEntry>,
+    pub entries_size: Vec<u64>,
+    pub entries_file_count: Vec<usize>,
+    /// First `.git` directory found in each entry
+    pub entries_git_dir: Vec<Option<PathBuf>>,
+    /// Entries marked for deletion with their content, possibly in other directories, by path.
+    /// Paths are canonical because the browser starts from the canonical root and does not follow links
+    pub marked: HashMap<PathBuf, DeletionTarget>,
+    /// Whether something was deleted since the browser was opened
+    pub modified: bool,
+    /// Measure of the current directory in progress, its previous entries being kept meanwhile
+    pub task: Option<BrowseTask>,
+    /// Entry to highlight once the current directory is measured
+    pending_highlight: Option<PathBuf>,
+}
+
+impl BrowseState {
+    pub fn new(root: &Path) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let mut browse_state = Self {
+            root: root.clone(),
+            current_dir: root,
+            ..Default::default()
+        };
+        browse_state.refresh();
+        browse_state
+    }
+
+    /// Start listing the children of the current directory, keeping the highlighted entry if it is still there.
+    /// The list is replaced by `update` once they are measured
+    pub fn refresh(&mut self) {
+        if self.pending_highlight.is_none() {
+            self.pending_highlight = self.entries.selected_index().map(|idx| self.entries.items[idx].path().to_path_buf());
+        }
+        self.cancel();
+        self.task = Some(BrowseTask::spawn(self.current_dir.clone()));
+    }
+
+    /// Pick up the children of the current directory once measured
+    pub fn update(&mut self) {
+        let Some(children) = self.task.as_ref().and_then(BrowseTask::take_result) else { return; };
+        self.task = None;
+
+        self.entries_size = children.iter().map(|(_, content)| content.size).collect();
+        self.entries_file_count = children.iter().map(|(_, content)| content.file_count).collect();
+        let (entries, contents): (Vec<_>, Vec<_>) = children.into_iter().unzip();
+        self.entries_git_dir = contents.into_iter().map(|content| content.git_dir).collect();
+        self.entries.set_items(entries);
+
+        let highlighted = self.pending_highlight.take()
+            .and_then(|path| self.entries.items.iter().position(|entry| entry.path() == path));
+        if let Some(idx) = highlighted {
+            self.entries.select_index(idx);
+        }
+    }
+
+    /// Wait for the current directory to be measured, for the callers which need its entries right away
+    pub fn wait(&mut self) {
+        while self.task.is_some() {
+            self.update();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.entries_size.iter().sum()
+    }
+
+    pub fn marked_size(&self) -> u64 {
+        self.outermost_marked().map(|target| target.size).sum()
+    }
+
+    /// Marked entries which are not inside another marked entry, whose deletion removes them already
+    pub fn outermost_marked(&self) -> impl Iterator<Item = &DeletionTarget> {
+        self.marked
+            .iter()
+            .filter(|(path, _)| !path.ancestors().skip(1).any(|ancestor| self.marked.contains_key(ancestor)))
+            .map(|(_, marked)| marked)
+    }
+
+    pub fn is_marked(&self, idx: usize) -> bool {
+        self.marked.contains_key(self.entries.items[idx].path())
+    }
+
+    pub fn set_marked(&mut self, idx: usize, marked: bool) {
+        let entry = &self.entries.items[idx];
+        if marked {
+            let target = DeletionTarget {
+                entry: entry.clone(),
+                file_count: self.entries_file_count[idx],
+                size: self.entries_size[idx],
+                git_dir: self.entries_git_dir[idx].clone(),
+            };
+            self.marked.insert(entry.path().to_path_buf(), target);
+        } else {
+            self.marked.remove(entry.path());
+        }
+    }
+
+    /// Go into the highlighted directory
+    pub fn enter_selected(&mut self) {
+        let Some(entry) = self.entries.selected_index().map(|idx| &self.entries.items[idx]) else { return; };
+        if !entry.file_type().is_dir() {
+            return;
+        }
+        self.current_dir = entry.path().to_path_buf();
+        self.clear_entries();
+        self.refresh();
+    }
+
+    /// Go back to the parent directory, highlighting the directory we come from
+    pub fn go_to_parent(&mut self) {
+        if self.current_dir == self.root {
+            return;
+        }
+        let Some(parent) = self.current_dir.parent().map(Path::to_path_buf) else { return; };
+        self.pending_highlight = Some(std::mem::replace(&mut self.current_dir, parent));
+        self.clear_entries();
+        self.refresh();
+    }
+
+    /// Forget the entries of the previous directory while the current one is measured
+    fn clear_entries(&mut self) {
+        self.entries.set_items(vec![]);
+        self.entries_size.clear();
+        self.entries_file_count.clear();
+        self.entries_git_dir.clear();
+    }
+
+    /// Stop measuring the current directory, e.g. when leaving the browser
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.cancel();
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use log::warn;
+
+/// Number of entries kept in a history file
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+/// Previously entered values of a text input, oldest first, persisted one per line
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    /// File the history is saved to, in memory only if `None`
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+    /// Entry currently recalled, `None` when editing a new value
+    cursor: Option<usize>,
+    /// Value being edited before recalling the history
+    draft: String,
+}
+
+impl History {
+    /// Load the history saved in `path`, starting empty if it cannot be read
+    pub fn load(path: PathBuf) -> Self {
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => content.lines().map(String::from).collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => {
+                warn!("Unable to read the history {}: {}", path.display(), e);
+                vec![]
+            },
+        };
+
+        Self { path: Some(path), entries, ..Default::default() }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record a value as the most recent one and save the history
+    pub fn push(&mut self, entry: impl Into<String>) {
+        let entry = entry.into();
+        self.cursor = None;
+        if entry.is_empty() || entry.contains('\n') {
+            return;
+        }
+
+        self.entries.retain(|existing| *existing != entry);
+        self.entries.push(entry);
+        let overflow = self.entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+        self.entries.drain(..overflow);
+
+        if let Err(e) = self.save() {
+            warn!("Unable to save the history: {}", e);
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else { return Ok(()); };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        std::fs::write(path, content)
+    }
+
+    /// Recall the entry before the current one, `current` being kept as the draft when leaving it.
+    /// The most recent entry is skipped if it is the draft itself
+    pub fn recall_previous(&mut self, current: &str) -> Option<&str> {
+        let cursor = match self.cursor {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                let last = self.entries.len() - 1;
+                if self.entries[last] == current { last.saturating_sub(1) } else { last }
+            },
+            Some(cursor) => cursor.saturating_sub(1),
+        };
+        self.cursor = Some(cursor);
+        Some(&self.entries[cursor])
+    }
+
+    /// Recall the entry after the current one, back to the draft after the most recent entry
+    pub fn recall_next(&mut self) -> Option<&str> {
+        let cursor = self.cursor?;
+        if cursor + 1 < self.entries.len() {
+            self.cursor = Some(cursor + 1);
+            Some(&self.entries[cursor + 1])
+        } else {
+            self.cursor = None;
+            Some(&self.draft)
+        }
+    }
+
+    /// Forget the recalled entry, e.g. when the input is edited
+    pub fn reset_cursor(&mut self) {
+        self.cursor = None;
+    }
+}
@@ -1,108 +1,646 @@
 use log::{debug, warn};
 use tui_textarea::TextArea;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use crate::utils::{statefull_list::StatefulList, key_display::KeyEventWrapper, focusable_text_area::FocusableTextArea};
+use std::time::{Duration, Instant};
+use crate::utils::{statefull_list::{StatefulList, Navigable}, key_display::{KeyEventWrapper, KeySequenceWrapper}, focusable_text_area::FocusableTextArea};
 
-use self::actions::Actions;
+use self::actions::{ActionContext, Actions, KeyBindings, KeyMatch};
+use strum::IntoEnumIterator;
 use crate::app::actions::Action;
-use crossterm::event::KeyEvent;
-use ratatui::widgets::ScrollbarState;
-use crate::utils::walker::{get_dir_list_from_path, count_and_size, delete_entries};
+use crate::config::Config;
+use crate::utils::walker::{DeletionReport, DeletionSettings, DeletionStrategy, DeletionTarget};
+use eyre::{Result, eyre};
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind, MouseButton};
+use ratatui::layout::Rect;
+use crate::utils::walker::{get_dir_list_from_path, measure_content, delete_entries, restore_from_trash};
+use crate::utils::guard::DeletionGuard;
+use crate::utils::quarantine;
+use crate::utils::os_path::{canonicalize_parent, os_str_bytes, tilde_path, is_lossy, expand_path, validate_roots, complete_directory, split_roots, dedupe_roots, common_ancestor, ROOTS_SEPARATOR};
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use self::tree::{TreeRow, build_tree_rows};
+use self::preview::{Preview, PreviewTask};
+use self::browse::BrowseState;
+use self::theme::Theme;
+use self::toasts::Toasts;
+use self::history::History;
+use self::searches::{SavedSearch, SavedSearches};
+use self::undo::{DeletionBatch, DeletionHistory};
 
 pub mod actions;
+pub mod browse;
+pub mod history;
+pub mod preview;
+pub mod searches;
+pub mod theme;
+pub mod toasts;
+pub mod tree;
 pub mod ui;
+pub mod undo;
 
+/// Time allowed between two keys of a sequence
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+/// Number of rows scrolled by a mouse wheel step
+const MOUSE_SCROLL_STEP: usize = 3;
+
+// Options left unset fall back to the configuration files, see `wiper config show`
 #[derive(clap::Parser, Clone, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_precedence_over_arg = true)]
 pub struct Arguments {
-    #[arg(help("root Path to search"), value_hint = clap::ValueHint::DirPath)]
-    pub root_path: Option<PathBuf>,
-    #[arg(help("regex filter"), long, default_value = "^node_modules$")]
-    pub regex_filter: String,
+    #[arg(help("root paths to search, scanned together"), value_hint = clap::ValueHint::DirPath)]
+    pub root_path: Vec<PathBuf>,
+    #[arg(help("regex filter [default: ^node_modules$]"), long)]
+    pub regex_filter: Option<String>,
+    #[arg(help("use a regex filter preset from the configuration"), long, conflicts_with = "regex_filter")]
+    pub preset: Option<String>,
     #[arg(
         short,
         long,
-        default_value_t = true,
-        help("do not search on subfolders")
+        help("do not search on subfolders of matched entries [default: true]")
     )]
-    pub prune: bool,
+    pub prune: Option<bool>,
+    #[arg(help("load a search saved from the interface, other options overriding it"), long)]
+    pub search: Option<String>,
+    #[arg(help("how entries are deleted [default: trash]"), long, value_enum)]
+    pub deletion_strategy: Option<DeletionStrategy>,
+    #[arg(help("allow deleting entries containing a .git directory [default: false]"), long)]
+    pub allow_git_deletion: Option<bool>,
+    #[arg(help("color theme [default: dark]"), long)]
+    pub theme: Option<String>,
+    #[arg(help("user configuration file, defaults to ~/.config/wiper/config.toml"), long, value_hint = clap::ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+    #[arg(help("log file, rotated when it grows too large"), long, value_hint = clap::ValueHint::FilePath)]
+    pub log_file: Option<PathBuf>,
+    #[arg(help("log more details, -vv for everything (RUST_LOG takes precedence)"), short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Delete the quarantined entries for good
+    Purge {
+        /// Only purge the entries quarantined for longer than this age, e.g. 12h, 7d or 2w
+        #[arg(long, value_parser = crate::utils::quarantine::parse_age)]
+        older_than: chrono::Duration,
+        /// List the entries that would be purged without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Move a quarantined entry back to its original path
+    Restore {
+        /// Id of the entry, the name of its directory in the quarantine
+        id: String,
+    },
+    /// List the deletions recorded in the audit log, oldest first
+    History {
+        /// Only the deletions more recent than this age, e.g. 12h, 7d or 2w
+        #[arg(long, value_parser = crate::utils::quarantine::parse_age)]
+        since: Option<chrono::Duration>,
+        /// Only the deletions whose path contains this text
+        #[arg(long)]
+        path: Option<String>,
+        /// Only the deletions made by this user, directly or through sudo
+        #[arg(long)]
+        user: Option<String>,
+        /// Only the failed and refused deletions
+        #[arg(long)]
+        failed: bool,
+        /// Only the most recent deletions
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+        /// Print the records as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective configuration and where each value comes from
+    Show,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum AppReturn {
     Exit,
+    /// Stop the process until it is resumed, as Ctrl-Z does outside of raw mode
+    Suspend,
     Continue,
 }
 
+/// What the application is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AppMode {
+    /// List the entries matching the filter
+    #[default]
+    Filter,
+    /// Browse directories sorted by disk usage
+    Browse,
+}
+
+/// How the matched entries are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    /// Flat list sorted by size
+    #[default]
+    List,
+    /// Entries grouped under their ancestor directories
+    Tree,
+}
+
 #[derive(Clone)]
 pub struct AppState {
-    pub path: PathBuf,
+    pub mode: AppMode,
+    pub browse: BrowseState,
+    /// Directories scanned together, none of them inside another
+    pub roots: Vec<PathBuf>,
     pub regex_filter: String,
+    pub prune: bool,
+    pub deletion_strategy: DeletionStrategy,
+    /// Quarantine directory and audit log of the deletions
+    pub deletion_settings: DeletionSettings,
+    /// Directory of the histories and saved searches
+    pub state_dir: PathBuf,
     pub entries: StatefulList<walkdir::DirEntry>,
     pub entries_size: Vec<u64>,
-    pub selected_entries_idx: HashSet<usize>,
+    pub entries_file_count: Vec<usize>,
+    /// First `.git` directory found in each entry
+    pub entries_git_dir: Vec<Option<PathBuf>>,
+    /// Path of each entry with its parent canonical, as recorded by the deletions, used as a stable key across rescans
+    pub entries_path: Vec<PathBuf>,
+    /// Index in `roots` of the root of each entry
+    pub entries_root: Vec<usize>,
+    /// Paths of the selected entries, as in `entries_path`
+    pub selected_entries: HashSet<PathBuf>,
+    /// Selected paths dropped by the last rescan because they are no longer listed
+    pub dropped_selection: Vec<PathBuf>,
+    /// Visible row from which range selection starts (last toggled entry)
+    pub selection_anchor: Option<usize>,
     pub path_text_area: FocusableTextArea<'static>,
     pub filter_text_area: FocusableTextArea<'static>,
+    pub select_text_area: FocusableTextArea<'static>,
+    pub search_text_area: FocusableTextArea<'static>,
+    /// Name under which the current search is saved
+    pub save_search_text_area: FocusableTextArea<'static>,
+    /// Entries matching the search, best match first
+    pub search_hits: Vec<usize>,
+    /// Matched character indices in the displayed path of each hit
+    pub search_highlights: HashMap<usize, Vec<usize>>,
+    /// Position in `search_hits` of the last jump
+    pub search_hit_cursor: usize,
+    /// Area of the entry list in the last drawn frame, used for paging and mouse handling
+    pub list_area: Rect,
+    pub view_mode: ViewMode,
+    /// Rows of the tree view, rebuilt from the visible entries
+    pub tree: StatefulList<TreeRow>,
+    /// Paths of the collapsed tree nodes
+    pub collapsed_nodes: HashSet<PathBuf>,
+    pub show_preview: bool,
+    pub show_logs: bool,
+    /// Display the canonical path of the entries instead of their path relative to the scan root
+    pub absolute_paths: bool,
+    /// Replaced by `~` in the displayed absolute paths
+    pub home_dir: Option<PathBuf>,
+    /// Preview of the last highlighted entry, computed only when the panel is shown
+    pub preview: Option<Preview>,
+    /// Preview of the highlighted row being computed, `preview` being outdated meanwhile
+    pub preview_task: Option<PreviewTask>,
+    /// Beginning of a key sequence waiting for its next key
+    pub pending_keys: Vec<KeyEvent>,
+    /// When the last pending key was pressed
+    pub pending_keys_time: Option<Instant>,
+    pub theme: Theme,
+    /// Notifications of the operations, displayed over the interface
+    pub toasts: Toasts,
+    /// Recently scanned roots, recalled in the path input
+    pub root_history: History,
+    /// Recently applied filters, recalled in the filter input
+    pub filter_history: History,
+    pub saved_searches: SavedSearches,
+    /// Names of the saved searches while the picker is open
+    pub search_picker: Option<StatefulList<String>>,
+    /// Recent deletions, restored from the trash by undo
+    pub deletions: DeletionHistory,
+    /// Indices of the deletion batches, most recent first, while they are displayed
+    pub deletions_view: Option<StatefulList<usize>>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
-            path: PathBuf::from("."),
+            mode: AppMode::default(),
+            browse: BrowseState::default(),
+            roots: vec![PathBuf::from(".")],
             regex_filter: "^node_modules$".to_string(),
+            prune: true,
+            deletion_strategy: DeletionStrategy::default(),
+            deletion_settings: DeletionSettings::default(),
+            state_dir: PathBuf::new(),
             entries: StatefulList::default(),
             entries_size: vec![],
-            selected_entries_idx: HashSet::new(),
+            entries_file_count: vec![],
+            entries_git_dir: vec![],
+            entries_path: vec![],
+            entries_root: vec![],
+            selected_entries: HashSet::new(),
+            dropped_selection: vec![],
+            selection_anchor: None,
             path_text_area: FocusableTextArea::default(),
-            filter_text_area: FocusableTextArea::default()
+            filter_text_area: FocusableTextArea::default(),
+            select_text_area: FocusableTextArea::default(),
+            search_text_area: FocusableTextArea::default(),
+            save_search_text_area: FocusableTextArea::default(),
+            search_hits: vec![],
+            search_highlights: HashMap::new(),
+            search_hit_cursor: 0,
+            list_area: Rect::default(),
+            view_mode: ViewMode::default(),
+            tree: StatefulList::default(),
+            collapsed_nodes: HashSet::new(),
+            show_preview: false,
+            show_logs: false,
+            absolute_paths: false,
+            home_dir: dirs::home_dir(),
+            preview: None,
+            preview_task: None,
+            pending_keys: vec![],
+            pending_keys_time: None,
+            theme: Theme::default(),
+            toasts: Toasts::default(),
+            root_history: History::default(),
+            filter_history: History::default(),
+            saved_searches: SavedSearches::default(),
+            search_picker: None,
+            deletions: DeletionHistory::default(),
+            deletions_view: None,
         }
     }
 }
 
+impl AppState {
+    pub fn is_selected(&self, idx: usize) -> bool {
+        self.selected_entries.contains(&self.entries_path[idx])
+    }
+
+    pub fn set_selected(&mut self, idx: usize, selected: bool) {
+        let path = self.entries_path[idx].clone();
+        if selected {
+            self.selected_entries.insert(path);
+        } else {
+            self.selected_entries.remove(&path);
+        }
+    }
+
+    /// Indices of the selected entries in the current list
+    pub fn selected_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.entries_path.len()).filter(|idx| self.is_selected(*idx))
+    }
+
+    /// Selected entries which are not inside another selected entry, whose deletion removes them already
+    pub fn outermost_selected_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected_indices().filter(|&idx| {
+            !self.entries_path[idx].ancestors().skip(1).any(|ancestor| self.selected_entries.contains(ancestor))
+        })
+    }
+
+    /// Directories of wiper which are never scanned, canonical: the state directory and the quarantine,
+    /// whose entries would match again
+    pub fn excluded_dirs(&self) -> Vec<PathBuf> {
+        [&self.state_dir, &self.deletion_settings.quarantine_dir]
+            .into_iter()
+            .filter_map(|dir| dir.canonicalize().ok())
+            .collect()
+    }
+
+    /// Entry `idx` with its measured content, to be deleted
+    pub fn deletion_target(&self, idx: usize) -> DeletionTarget {
+        DeletionTarget {
+            entry: self.entries.items[idx].clone(),
+            file_count: self.entries_file_count[idx],
+            size: self.entries_size[idx],
+            git_dir: self.entries_git_dir[idx].clone(),
+        }
+    }
+
+    /// Root of the entry `idx` as displayed in the root column
+    pub fn root_label(&self, idx: usize) -> String {
+        tilde_path(&self.roots[self.entries_root[idx]], self.home_dir.as_deref())
+    }
+
+    /// Path of an entry as displayed and searched: relative to its scan root,
+    /// or canonical with the home directory as `~`
+    pub fn display_path(&self, idx: usize) -> String {
+        if self.absolute_paths {
+            return tilde_path(&self.entries_path[idx], self.home_dir.as_deref());
+        }
+
+        let path = self.entries.items[idx].path();
+        match path.strip_prefix(&self.roots[self.entries_root[idx]]) {
+            Ok(relative) if relative.as_os_str().is_empty() => String::from("."),
+            Ok(relative) => relative.display().to_string(),
+            Err(_) => path.display().to_string(),
+        }
+    }
+
+    pub fn search_pattern(&self) -> &str {
+        &self.search_text_area.lines()[0]
+    }
+
+    /// Number of entry rows visible at once in the list
+    pub fn page_size(&self) -> usize {
+        // remove the borders
+        self.list_area.height.saturating_sub(2).max(1) as usize
+    }
+
+    /// List displayed by the current mode and view mode
+    pub fn active_list(&self) -> &dyn Navigable {
+        match (self.mode, self.view_mode) {
+            (AppMode::Browse, _) => &self.browse.entries,
+            (AppMode::Filter, ViewMode::List) => &self.entries,
+            (AppMode::Filter, ViewMode::Tree) => &self.tree,
+        }
+    }
+
+    pub fn active_list_mut(&mut self) -> &mut dyn Navigable {
+        match (self.mode, self.view_mode) {
+            (AppMode::Browse, _) => &mut self.browse.entries,
+            (AppMode::Filter, ViewMode::List) => &mut self.entries,
+            (AppMode::Filter, ViewMode::Tree) => &mut self.tree,
+        }
+    }
+
+    /// Entries covered by the displayed row at `position`, a tree node covers its whole subtree.
+    /// In browse mode these are indices of the browsed entries
+    pub fn row_entries(&self, position: usize) -> Vec<usize> {
+        if self.mode == AppMode::Browse {
+            return vec![self.browse.entries.visible_index(position)];
+        }
+
+        match self.view_mode {
+            ViewMode::List => vec![self.entries.visible_index(position)],
+            ViewMode::Tree => self.tree.items[position].entries.clone(),
+        }
+    }
+
+    /// Whether the entry `idx` is selected, or marked in browse mode
+    fn is_row_entry_selected(&self, idx: usize) -> bool {
+        match self.mode {
+            AppMode::Filter => self.is_selected(idx),
+            AppMode::Browse => self.browse.is_marked(idx),
+        }
+    }
+
+    fn set_row_entry_selected(&mut self, idx: usize, selected: bool) {
+        match self.mode {
+            AppMode::Filter => self.set_selected(idx, selected),
+            AppMode::Browse => self.browse.set_marked(idx, selected),
+        }
+    }
+
+    /// Select every entry of the row, or unselect them if they are all selected already
+    pub fn toggle_row(&mut self, position: usize) {
+        let row_entries = self.row_entries(position);
+        let all_selected = row_entries.iter().all(|idx| self.is_row_entry_selected(*idx));
+        for idx in row_entries {
+            self.set_row_entry_selected(idx, !all_selected);
+        }
+    }
+
+    /// Entries that would be affected by a select all, in the displayed list
+    fn displayed_entries(&self) -> Vec<usize> {
+        match self.mode {
+            AppMode::Filter => self.entries.visible_indices().collect(),
+            AppMode::Browse => self.browse.entries.visible_indices().collect(),
+        }
+    }
+
+    /// Move the cursor to the row displaying the entry `idx`
+    pub fn select_entry(&mut self, idx: usize) {
+        match self.view_mode {
+            ViewMode::List => self.entries.select_index(idx),
+            ViewMode::Tree => {
+                let position = self.tree.items.iter().position(|row| row.entry == Some(idx));
+                if position.is_some() {
+                    self.tree.state.select(position);
+                }
+            },
+        }
+    }
+
+    /// Rebuild the tree rows from the visible entries, keeping the cursor on the same node
+    pub fn update_tree(&mut self) {
+        let current_path = self.tree.state.selected().map(|position| self.tree.items[position].path.clone());
+        // nodes of several roots are grouped under their common ancestor
+        let roots = self.roots
+            .iter()
+            .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+            .collect::<Vec<_>>();
+        let root = common_ancestor(&roots);
+
+        let rows = build_tree_rows(
+            &root,
+            &self.entries_path,
+            &self.entries_size,
+            self.entries.visible_indices(),
+            &self.collapsed_nodes,
+        );
+        self.tree.set_items(rows);
+
+        if let Some(path) = current_path {
+            let position = self.tree.items.iter().position(|row| row.path == path);
+            if position.is_some() {
+                self.tree.state.select(position);
+            }
+        }
+    }
+
+    /// Path of the highlighted row, a directory node in tree view
+    pub fn highlighted_path(&self) -> Option<PathBuf> {
+        if self.mode == AppMode::Browse {
+            return self.browse.entries.selected_index().map(|idx| self.browse.entries.items[idx].path().to_path_buf());
+        }
+
+        match self.view_mode {
+            ViewMode::List => self.entries.selected_index().map(|idx| self.entries_path[idx].clone()),
+            ViewMode::Tree => self.tree.state.selected().map(|position| self.tree.items[position].path.clone()),
+        }
+    }
+
+    /// Start computing the preview of the highlighted row if it changed since the last call,
+    /// and pick up the preview once computed
+    pub fn update_preview(&mut self) {
+        let highlighted_path = self.highlighted_path();
+        if let Some(task) = &self.preview_task {
+            if Some(&task.path) == highlighted_path.as_ref() {
+                if let Some(preview) = task.take_result() {
+                    self.preview = Some(preview);
+                    self.preview_task = None;
+                }
+                return;
+            }
+        }
+        if self.preview_task.is_none() && self.preview.as_ref().map(|preview| &preview.path) == highlighted_path.as_ref() {
+            return;
+        }
+
+        self.reset_preview();
+        self.preview_task = highlighted_path.map(PreviewTask::spawn);
+    }
+
+    /// Forget the preview, e.g. when its entry changed, stopping its computation
+    pub fn reset_preview(&mut self) {
+        if let Some(task) = self.preview_task.take() {
+            task.cancel();
+        }
+        self.preview = None;
+    }
+
+    /// Collapse or expand the highlighted tree node
+    pub fn set_current_node_collapsed(&mut self, collapsed: bool) {
+        if self.mode != AppMode::Filter || self.view_mode != ViewMode::Tree {
+            return;
+        }
+        let Some(row) = self.tree.state.selected().map(|position| &self.tree.items[position]) else { return; };
+        if !row.has_children {
+            return;
+        }
+
+        if collapsed {
+            self.collapsed_nodes.insert(row.path.clone());
+        } else {
+            self.collapsed_nodes.remove(&row.path.clone());
+        }
+        self.update_tree();
+    }
+}
+
+/// Whether `path` is one of `dirs` or inside one of them
+fn is_in_any(path: &std::path::Path, dirs: &[PathBuf]) -> bool {
+    dirs.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Roots as typed in the path input
+fn roots_text(roots: &[PathBuf]) -> String {
+    roots
+        .iter()
+        .map(|root| root.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(&ROOTS_SEPARATOR.to_string())
+}
+
 /// The main application, containing the state
 pub struct App {
     /// Contextual actions
-    actions: Actions,
+    actions: HashMap<ActionContext, Actions>,
     state: AppState,
 }
 
 impl App {
-    pub fn new_from_args(args: &Arguments) -> Self {
+    pub fn new_from_config(config: &Config) -> Result<Self> {
 
-        let path = args.root_path.clone().unwrap_or_else(|| PathBuf::from("."));
-        let regex_filter = args.regex_filter.clone();
-        let state = AppState {
-            path: path.clone(),
-            regex_filter: regex_filter.clone(),
+        let home_dir = dirs::home_dir();
+        // the paths of the configuration are expanded already
+        let roots = config.root_path.value.clone();
+        if roots.is_empty() {
+            return Err(eyre!("No root path to scan (from {})", config.root_path.source));
+        }
+        let (roots, dropped_roots) = dedupe_roots(&roots);
+        let guard = DeletionGuard {
+            roots: roots.clone(),
+            home_dir: home_dir.clone(),
+            quarantine_dir: Some(config.quarantine_dir.value.clone()),
+            allow_git: config.allow_git_deletion.value,
+            denylist: config.denylist.value.clone(),
+        };
+        let regex_filter = config.regex_filter.value.clone();
+        let theme = config.theme()?;
+        // a broken file only matters to `--search`, which fails in `Config::load`
+        let (saved_searches, searches_error) = match SavedSearches::load(&config.state_dir.value) {
+            Ok(saved_searches) => (saved_searches, None),
+            Err(e) => {
+                warn!("{:#}", e);
+                (SavedSearches::read_only(&config.state_dir.value), Some(e))
+            },
+        };
+        let mut state = AppState {
             path_text_area: FocusableTextArea::new(
-                [path.to_str().unwrap()],
-                "Relative Path (Active - Esc to unfocus)",
-                "Relative Path (Inactive - p to focus)"),
+                [roots_text(&roots)],
+                "Root Paths (Active - Esc to unfocus)",
+                "Root Paths (Inactive - p to focus)"),
+            roots,
+            regex_filter: regex_filter.clone(),
+            prune: config.prune.value,
+            deletion_strategy: config.deletion_strategy.value,
+            deletion_settings: DeletionSettings {
+                quarantine_dir: config.quarantine_dir.value.clone(),
+                audit_log: config.audit_log.value.clone(),
+                guard,
+            },
+            state_dir: config.state_dir.value.clone(),
             filter_text_area: FocusableTextArea::new(
-                ["^node_modules$"],
+                [regex_filter.as_str()],
                 "Filter (Active - Esc to unfocus)",
                 "Filter (Inactive - f to focus)"),
+            select_text_area: FocusableTextArea::new(
+                [""],
+                "Select matching (Active - Esc to apply)",
+                "Select matching (Inactive - s to focus)"),
+            search_text_area: FocusableTextArea::new(
+                [""],
+                "Search (Active - Esc to close)",
+                "Search (n/N to jump between hits, / to edit)"),
+            save_search_text_area: FocusableTextArea::new(
+                [""],
+                "Save search as (Active - Esc to save, empty to cancel)",
+                "Save search as"),
+            theme,
+            home_dir,
+            root_history: History::load(config.state_dir.value.join("roots_history")),
+            filter_history: History::load(config.state_dir.value.join("filters_history")),
+            saved_searches,
             ..Default::default()
         };
+        for text_area in [&mut state.path_text_area, &mut state.filter_text_area, &mut state.select_text_area, &mut state.search_text_area, &mut state.save_search_text_area] {
+            text_area.set_styles(theme.focused_input, theme.unfocused_input);
+        }
         
+        let key_bindings = KeyBindings::from_config(&config.key_bindings(), &config.leader.value)
+            .map_err(|e| eyre!("Invalid key bindings in the configuration: {}", e))?;
+
+        let actions = ActionContext::iter()
+            .map(|context| {
+                Actions::new(context.actions(), key_bindings.clone())
+                    .map(|actions| (context, actions))
+                    .map_err(|e| eyre!("{} (in {} context)", e, context))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
         let mut app = Self {
-            actions: Actions::from_iter([
-                Action::Quit,
-                Action::DeleteSelectedEntries,
-                Action::ToggleCurrent,
-                Action::Up,
-                Action::Down,
-                Action::EditPath,
-                Action::EditFilter,
-                Action::UnfocusTextArea,
-            ]),
+            actions,
             state,
         };
 
+        app.warn_dropped_roots(&dropped_roots);
+        if let Some(e) = searches_error {
+            app.state.toasts.warn(format!("{}, starting without saved searches (details in the log)", e));
+        }
+        match validate_roots(&app.state.roots) {
+            Ok(()) => app.record_roots(),
+            Err(e) => app.state.toasts.error(format!("Not scanning {}", e)),
+        }
+        if regex::bytes::Regex::new(&app.state.regex_filter).is_ok() {
+            app.state.filter_history.push(app.state.regex_filter.clone());
+        }
         app.scan_dir_update();
 
-        app
+        Ok(app)
     }
     /// Handle a user action
     pub fn key_event(&mut self, key_event: KeyEvent) -> AppReturn {
@@ -111,28 +649,80 @@ impl App {
             return AppReturn::Continue;
         }
 
-        let optional_action = self.actions.find(key_event);
+        self.state.pending_keys.push(key_event);
+        let optional_action = match self.actions().find(&self.state.pending_keys) {
+            KeyMatch::Action(action) => Some(action),
+            KeyMatch::Pending => {
+                self.state.pending_keys_time = Some(Instant::now());
+                return AppReturn::Continue;
+            },
+            KeyMatch::None => None,
+        };
+        let keys = std::mem::take(&mut self.state.pending_keys);
+        self.state.pending_keys_time = None;
+
+        // the abandoned sequence is dropped (or typed), the last key may start another one
+        if optional_action.is_none() && keys.len() > 1 {
+            debug!("No action associated to {}", KeySequenceWrapper(&keys));
+            if self.context().is_text_input() {
+                for key in &keys[..keys.len() - 1] {
+                    self.input_text(*key);
+                }
+            }
+            return self.key_event(key_event);
+        }
 
-        if self.state.path_text_area.focused || self.state.filter_text_area.focused {
+        if self.context().is_text_input() {
             if let Some(Action::UnfocusTextArea) = optional_action {
 
+                // keep the narrowed list, n/N still jump between hits
+                if self.state.search_text_area.focused {
+                    self.state.search_text_area.set_focus(false);
+                    return AppReturn::Continue;
+                }
+
+                if self.state.save_search_text_area.focused {
+                    let name = self.state.save_search_text_area.lines()[0].trim().to_string();
+                    self.state.save_search_text_area.set_focus(false);
+                    self.state.save_search_text_area.set_text("");
+                    if !name.is_empty() {
+                        self.save_search(&name);
+                    }
+                    return AppReturn::Continue;
+                }
+
+                // apply the pattern without rescanning
+                if self.state.select_text_area.focused {
+                    let pattern = self.state.select_text_area.lines()[0].clone();
+                    self.state.select_text_area.set_focus(false);
+                    self.select_matching(&pattern);
+                    return AppReturn::Continue;
+                }
+
                 // update path state value from text area 
                 if self.state.path_text_area.focused {
-                    self.state.path = PathBuf::from(self.state.path_text_area.lines()[0].clone());
                     self.state.path_text_area.set_focus(false);
+                    if !self.apply_path_input() {
+                        return AppReturn::Continue;
+                    }
                 } else {
                     self.state.regex_filter = self.state.filter_text_area.lines()[0].clone();
                     self.state.filter_text_area.set_focus(false);
+                    if regex::bytes::Regex::new(&self.state.regex_filter).is_ok() {
+                        self.state.filter_history.push(self.state.regex_filter.clone());
+                    }
                 }
                 self.scan_dir_update();
 
                 return AppReturn::Continue;
             }
-            
-            let input = tui_textarea::Input::from(key_event);
-            self.state.path_text_area.input(input.clone());
-            self.state.filter_text_area.input(input);
 
+            match optional_action {
+                Some(Action::CompletePath) => self.complete_path(),
+                Some(Action::PreviousHistoryEntry) => self.recall_history(true),
+                Some(Action::NextHistoryEntry) => self.recall_history(false),
+                _ => self.input_text(key_event),
+            }
             return AppReturn::Continue;
         }
 
@@ -144,89 +734,750 @@ impl App {
         let action = optional_action.unwrap();
         debug!("Run action [{:?}]", action);
 
+        if let Some(view) = &mut self.state.deletions_view {
+            match action {
+                Action::Up => view.previous(),
+                Action::Down => view.next(),
+                Action::Undo => {
+                    if let Some(batch_idx) = view.selected_index().map(|idx| view.items[idx]) {
+                        self.undo_deletion(batch_idx);
+                    }
+                },
+                Action::ClosePicker => self.state.deletions_view = None,
+                _ => {},
+            }
+            return AppReturn::Continue;
+        }
+
+        if let Some(picker) = &mut self.state.search_picker {
+            match action {
+                Action::Up => picker.previous(),
+                Action::Down => picker.next(),
+                Action::LoadSearch => {
+                    if let Some(name) = picker.selected_index().map(|idx| picker.items[idx].clone()) {
+                        self.state.search_picker = None;
+                        self.load_search(&name);
+                    }
+                },
+                Action::DeleteSearch => {
+                    if let Some(name) = picker.selected_index().map(|idx| picker.items[idx].clone()) {
+                        match self.state.saved_searches.remove(&name) {
+                            Ok(()) => self.state.toasts.info(format!("Deleted search {}", name)),
+                            Err(e) => self.state.toasts.error(format!("{:#}", e)),
+                        }
+                        self.open_search_picker();
+                    }
+                },
+                Action::ClosePicker => self.state.search_picker = None,
+                _ => {},
+            }
+            return AppReturn::Continue;
+        }
+
         match action {
+            Action::DeleteSelectedEntries if self.state.mode == AppMode::Browse => {
+                let targets = self.state.browse.outermost_marked().cloned().collect::<Vec<_>>();
+
+                let report = self.delete(&targets);
+
+                // the entries left, e.g. after a refusal, stay marked
+                let browse = &mut self.state.browse;
+                browse.marked.retain(|path, _| !is_in_any(path, &report.deleted));
+                browse.modified = true;
+                browse.refresh();
+                self.state.reset_preview();
+            },
             Action::DeleteSelectedEntries => {
                 let state = self.state();
-                let entries_to_delete = state.selected_entries_idx
-                    .iter()
-                    .map(|idx| state.entries.items[*idx].clone())
+                let targets = state.outermost_selected_indices()
+                    .map(|idx| state.deletion_target(idx))
                     .collect::<Vec<_>>();
 
-                if let Err(e) = delete_entries(&entries_to_delete) {
-                    warn!("Error while deleting entries: {}", e);
-                }
+                let report = self.delete(&targets);
+
+                // deleted entries are expected to disappear, do not report them as stale,
+                // the entries left, e.g. after a refusal, stay selected
+                self.state.selected_entries.retain(|path| !is_in_any(path, &report.deleted));
 
                 self.scan_dir_update();
             },
+            Action::Undo => match self.state.deletions.last_restorable() {
+                Some(batch_idx) => self.undo_deletion(batch_idx),
+                None => self.state.toasts.info("No deletion to undo, only trashed or quarantined entries can be restored"),
+            },
+            Action::ShowDeletions => {
+                let mut view = StatefulList::default();
+                // most recent first
+                view.set_items((0..self.state.deletions.batches().len()).rev().collect());
+                self.state.deletions_view = Some(view);
+            },
             Action::ToggleCurrent => {
                 let state = self.state_mut();
-                let current_idx = state.entries.state.selected();
-                if let Some(idx) = current_idx {
-                    if state.selected_entries_idx.contains(&idx) {
-                        state.selected_entries_idx.remove(&idx);
-                    } else {
-                        state.selected_entries_idx.insert(idx);
-                    }
+                let current_position = state.active_list().list_state().selected();
+                if let Some(position) = current_position {
+                    state.toggle_row(position);
+                }
+                state.selection_anchor = current_position;
+            },
+            Action::SelectAll => {
+                let state = self.state_mut();
+                for idx in state.displayed_entries() {
+                    state.set_row_entry_selected(idx, true);
+                }
+            },
+            Action::SelectNone => {
+                let state = self.state_mut();
+                match state.mode {
+                    AppMode::Filter => state.selected_entries.clear(),
+                    AppMode::Browse => state.browse.marked.clear(),
                 }
+                state.selection_anchor = None;
+            },
+            Action::InvertSelection => {
+                let state = self.state_mut();
+                for idx in state.displayed_entries() {
+                    let selected = state.is_row_entry_selected(idx);
+                    state.set_row_entry_selected(idx, !selected);
+                }
+            },
+            Action::SelectRangeUp => {
+                self.extend_selection(|idx, _| idx.saturating_sub(1));
+            },
+            Action::SelectRangeDown => {
+                self.extend_selection(|idx, len| (idx + 1).min(len - 1));
+            },
+            Action::SelectMatching => {
+                self.state.select_text_area.set_focus(true);
+            },
+            Action::Search => {
+                self.state.search_text_area.set_focus(true);
+            },
+            Action::SaveSearch => {
+                self.state.save_search_text_area.set_focus(true);
+            },
+            Action::OpenSearchPicker => {
+                self.open_search_picker();
+            },
+            // handled by the picker
+            Action::LoadSearch | Action::DeleteSearch | Action::ClosePicker => {},
+            Action::NextMatch => {
+                self.jump_to_hit(1);
+            },
+            Action::PreviousMatch => {
+                self.jump_to_hit(-1);
             },
             Action::Up => {
                 let state = self.state_mut();
-                state.entries.previous();
+                state.active_list_mut().previous();
             },
             Action::Down => {
                 let state = self.state_mut();
-                state.entries.next();
+                state.active_list_mut().next();
+            },
+            Action::PageUp => {
+                let state = self.state_mut();
+                let page_size = state.page_size();
+                state.active_list_mut().backward(page_size);
+            },
+            Action::PageDown => {
+                let state = self.state_mut();
+                let page_size = state.page_size();
+                state.active_list_mut().forward(page_size);
+            },
+            Action::HalfPageUp => {
+                let state = self.state_mut();
+                let page_size = state.page_size();
+                state.active_list_mut().backward(page_size / 2);
+            },
+            Action::HalfPageDown => {
+                let state = self.state_mut();
+                let page_size = state.page_size();
+                state.active_list_mut().forward(page_size / 2);
+            },
+            Action::First => {
+                self.state.active_list_mut().first();
+            },
+            Action::Last => {
+                self.state.active_list_mut().last();
+            },
+            Action::ToggleTreeView => {
+                let state = self.state_mut();
+                state.view_mode = match state.view_mode {
+                    ViewMode::List => ViewMode::Tree,
+                    ViewMode::Tree => ViewMode::List,
+                };
+                state.selection_anchor = None;
+                state.update_tree();
+            },
+            Action::CollapseNode => {
+                self.state.set_current_node_collapsed(true);
+            },
+            Action::ExpandNode => {
+                self.state.set_current_node_collapsed(false);
+            },
+            Action::TogglePreview => {
+                self.state.show_preview = !self.state.show_preview;
+            },
+            Action::ToggleAbsolutePaths => {
+                self.state.absolute_paths = !self.state.absolute_paths;
+                // the highlighted characters depend on the displayed path
+                if !self.state.search_pattern().is_empty() {
+                    self.update_search();
+                }
+            },
+            Action::ToggleLogs => {
+                self.state.show_logs = !self.state.show_logs;
+            },
+            Action::ToggleBrowseMode => {
+                let state = self.state_mut();
+                state.selection_anchor = None;
+                state.reset_preview();
+                match state.mode {
+                    AppMode::Filter => {
+                        // browse the root of the highlighted entry
+                        let root = state.entries.selected_index().map_or(0, |idx| state.entries_root[idx]);
+                        state.browse = BrowseState::new(&state.roots[root]);
+                        state.mode = AppMode::Browse;
+                    },
+                    AppMode::Browse => {
+                        state.browse.cancel();
+                        state.mode = AppMode::Filter;
+                        // the matched entries may have been deleted from the browser
+                        if state.browse.modified {
+                            self.scan_dir_update();
+                        }
+                    },
+                }
+            },
+            Action::EnterDirectory => {
+                if self.state.mode == AppMode::Browse {
+                    self.state.browse.enter_selected();
+                    self.state.selection_anchor = None;
+                }
+            },
+            Action::ParentDirectory => {
+                if self.state.mode == AppMode::Browse {
+                    self.state.browse.go_to_parent();
+                    self.state.selection_anchor = None;
+                }
             },
             Action::Quit => {
                 return AppReturn::Exit;
             },
+            Action::Suspend => {
+                return AppReturn::Suspend;
+            },
             Action::EditPath => {
                 self.state.path_text_area.set_focus(true);
+                self.update_path_status();
             },
             Action::EditFilter => {
                 self.state.filter_text_area.set_focus(true);
             },
             // Should not happen because we check if we are focused before
+            Action::CompletePath | Action::PreviousHistoryEntry | Action::NextHistoryEntry => {},
             Action::UnfocusTextArea => {
                 self.state.path_text_area.set_focus(false);
                 self.state.filter_text_area.set_focus(false);
+                self.state.select_text_area.set_focus(false);
+                self.state.search_text_area.set_focus(false);
+                self.state.save_search_text_area.set_focus(false);
             },
         }
         AppReturn::Continue
     }
 
+    /// Forward a key to the focused text area
+    fn input_text(&mut self, key_event: KeyEvent) {
+        let input = tui_textarea::Input::from(key_event);
+        if self.state.path_text_area.focused {
+            self.state.path_text_area.input(input.clone());
+            self.state.root_history.reset_cursor();
+            self.update_path_status();
+        }
+        if self.state.filter_text_area.focused {
+            self.state.filter_text_area.input(input.clone());
+            self.state.filter_history.reset_cursor();
+        }
+        self.state.select_text_area.input(input.clone());
+        self.state.save_search_text_area.input(input.clone());
+
+        // the search is incremental
+        if self.state.search_text_area.focused {
+            self.state.search_text_area.input(input);
+            self.update_search();
+            self.jump_to_hit(0);
+        }
+    }
+
+    /// Roots typed in the path input, expanded
+    fn input_roots(&self) -> Vec<PathBuf> {
+        split_roots(&self.state.path_text_area.lines()[0])
+            .map(|root| expand_path(root, self.state.home_dir.as_deref()))
+            .collect()
+    }
+
+    /// Show in the title of the path input why the typed roots cannot be scanned
+    fn update_path_status(&mut self) {
+        let status = validate_roots(&self.input_roots()).err();
+        self.state.path_text_area.set_status(status);
+    }
+
+    /// Complete the directory name being typed in the path input, listing the candidates when ambiguous
+    fn complete_path(&mut self) {
+        let input = self.state.path_text_area.lines()[0].clone();
+        // only the last root is completed
+        let (previous_roots, last_root) = match input.rfind(ROOTS_SEPARATOR) {
+            Some(idx) => input.split_at(idx + ROOTS_SEPARATOR.len_utf8()),
+            None => ("", input.as_str()),
+        };
+        let mut completion = complete_directory(last_root, self.state.home_dir.as_deref());
+        completion.text.insert_str(0, previous_roots);
+
+        if completion.candidates.len() > 1 && completion.text == input {
+            const SHOWN_CANDIDATES: usize = 8;
+            let mut candidates = completion.candidates[..completion.candidates.len().min(SHOWN_CANDIDATES)].join("  ");
+            if completion.candidates.len() > SHOWN_CANDIDATES {
+                candidates.push_str("  …");
+            }
+            self.state.toasts.info(candidates);
+        }
+
+        self.state.path_text_area.set_text(completion.text);
+        self.state.root_history.reset_cursor();
+        self.update_path_status();
+    }
+
+    /// Replace the focused path or filter input by its previous or next recent value
+    fn recall_history(&mut self, previous: bool) {
+        let state = &mut self.state;
+        let (text_area, history) = if state.path_text_area.focused {
+            (&mut state.path_text_area, &mut state.root_history)
+        } else {
+            (&mut state.filter_text_area, &mut state.filter_history)
+        };
+
+        let input = text_area.lines()[0].clone();
+        let recalled = if previous {
+            history.recall_previous(&input)
+        } else {
+            history.recall_next()
+        };
+
+        if let Some(recalled) = recalled.map(String::from) {
+            text_area.set_text(recalled);
+            if state.path_text_area.focused {
+                self.update_path_status();
+            }
+        }
+    }
+
+    /// Save the roots, filter and options of the current search under `name`
+    fn save_search(&mut self, name: &str) {
+        let search = SavedSearch {
+            roots: self.state.roots
+                .iter()
+                .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+                .collect(),
+            regex_filter: self.state.regex_filter.clone(),
+            prune: self.state.prune,
+            deletion_strategy: self.state.deletion_strategy,
+        };
+
+        match self.state.saved_searches.insert(name, search) {
+            Ok(()) => self.state.toasts.info(format!("Saved search {}", name)),
+            Err(e) => self.state.toasts.error(format!("{:#}", e)),
+        }
+    }
+
+    /// List the saved searches, keeping the cursor on the same row
+    fn open_search_picker(&mut self) {
+        let position = self.state.search_picker.as_ref().and_then(|picker| picker.state.selected());
+        let mut picker = StatefulList::default();
+        picker.set_items(self.state.saved_searches.names());
+        if let Some(last) = picker.items.len().checked_sub(1) {
+            picker.state.select(Some(position.unwrap_or(0).min(last)));
+        }
+        self.state.search_picker = Some(picker);
+    }
+
+    /// Replace the roots, filter and options by those of a saved search and rescan
+    fn load_search(&mut self, name: &str) {
+        let Some(search) = self.state.saved_searches.get(name).cloned() else { return; };
+        if let Err(e) = validate_roots(&search.roots) {
+            self.state.toasts.error(format!("Not loading search {}, {}", name, e));
+            return;
+        }
+
+        self.state.path_text_area.set_text(roots_text(&search.roots));
+        self.state.filter_text_area.set_text(search.regex_filter.clone());
+        self.state.roots = search.roots;
+        self.state.regex_filter = search.regex_filter;
+        self.state.prune = search.prune;
+        self.state.deletion_strategy = search.deletion_strategy;
+        self.record_roots();
+        self.state.filter_history.push(self.state.regex_filter.clone());
+
+        self.scan_dir_update();
+        self.state.toasts.info(format!("Loaded search {}", name));
+    }
+
+    /// Use the roots typed in the path input, restoring the current ones if one of them is not a readable directory
+    fn apply_path_input(&mut self) -> bool {
+        let roots = self.input_roots();
+        self.state.path_text_area.set_status(None);
+
+        if let Err(e) = validate_roots(&roots) {
+            self.state.toasts.error(format!("Not scanning {}", e));
+            self.state.path_text_area.set_text(roots_text(&self.state.roots));
+            return false;
+        }
+
+        let (roots, dropped_roots) = dedupe_roots(&roots);
+        self.warn_dropped_roots(&dropped_roots);
+        self.state.roots = roots;
+        self.record_roots();
+        true
+    }
+
+    fn warn_dropped_roots(&mut self, dropped_roots: &[(PathBuf, PathBuf)]) {
+        for (root, covering_root) in dropped_roots {
+            self.state.toasts.warn(format!("{} is already covered by {}", root.display(), covering_root.display()));
+        }
+    }
+
+    /// Add the current roots to the history, unless their paths cannot be typed back
+    fn record_roots(&mut self) {
+        let mut roots = vec![];
+        for root in &self.state.roots {
+            match root.canonicalize() {
+                Ok(path) if !is_lossy(path.as_os_str()) => roots.push(tilde_path(&path, self.state.home_dir.as_deref())),
+                Ok(_) => return,
+                Err(e) => {
+                    warn!("Unable to resolve the root {}: {}", root.display(), e);
+                    return;
+                },
+            }
+        }
+        self.state.root_history.push(roots.join(&ROOTS_SEPARATOR.to_string()));
+    }
+
+    /// Delete the entries with the configured strategy, recording the batch of those removed for undo,
+    /// even when a failure stopped it partway
+    fn delete(&mut self, targets: &[DeletionTarget]) -> DeletionReport {
+        let strategy = self.state.deletion_strategy;
+        // the roots change with the path input, they are protected as well
+        self.state.deletion_settings.guard.roots = self.state.roots.clone();
+
+        let report = delete_entries(targets, strategy, &self.state.deletion_settings);
+        // targets are deleted in order, up to the first failure
+        let size = targets[..report.deleted.len()].iter().map(|target| target.size).sum();
+        let deleted_count = report.deleted.len();
+        if deleted_count > 0 {
+            self.state.deletions.push(DeletionBatch { time: chrono::Local::now(), strategy, paths: report.deleted.clone(), size, restored: false });
+        }
+
+        match &report.error {
+            None => self.state.toasts.info(deletion_message(deleted_count, size, strategy)),
+            Some(e) if deleted_count == 0 => self.state.toasts.error(format!("Error while deleting entries: {}", e)),
+            Some(e) => self.state.toasts.error(format!(
+                "Error after {}: {}",
+                deletion_message(deleted_count, size, strategy),
+                e
+            )),
+        }
+        if let Some(e) = &report.audit_error {
+            let audit_log = self.state.deletion_settings.audit_log.display();
+            self.state.toasts.error(format!("Unable to record the deletion in the audit log {}: {}", audit_log, e));
+        }
+        report
+    }
+
+    /// Move the entries of a deletion batch back from the trash and rescan
+    fn undo_deletion(&mut self, batch_idx: usize) {
+        let Some(batch) = self.state.deletions.batch_mut(batch_idx) else { return; };
+        if !batch.is_restorable() {
+            let reason = if batch.restored { "already restored" } else { "deleted permanently" };
+            self.state.toasts.warn(format!("Cannot undo this deletion, its entries were {}", reason));
+            return;
+        }
+
+        let (restored, origin) = match batch.strategy {
+            DeletionStrategy::Quarantine => (quarantine::restore_paths(&self.state.deletion_settings.quarantine_dir, &batch.paths), "the quarantine"),
+            _ => (restore_from_trash(&batch.paths), "the trash"),
+        };
+        match restored {
+            Ok(restored_count) => {
+                batch.restored = true;
+                let message = format!("Restored {} of {} entries from {}", restored_count, batch.paths.len(), origin);
+                if restored_count < batch.paths.len() {
+                    self.state.toasts.warn(format!("{}, the others are no longer in {}", message, origin));
+                } else {
+                    self.state.toasts.info(message);
+                }
+            },
+            Err(e) => {
+                self.state.toasts.error(format!("Unable to restore the entries: {}", e));
+                return;
+            },
+        }
+
+        self.state.reset_preview();
+        if self.state.mode == AppMode::Browse {
+            self.state.browse.modified = true;
+            self.state.browse.refresh();
+        } else {
+            self.scan_dir_update();
+        }
+    }
+
+    /// Called at every iteration of the main loop, expires the toasts and abandons a key sequence left unfinished for too long
+    pub fn tick(&mut self) {
+        self.state.toasts.expire();
+
+        if self.state.pending_keys_time.is_some_and(|time| time.elapsed() >= KEY_SEQUENCE_TIMEOUT) {
+            debug!("Key sequence {} timed out", KeySequenceWrapper(&self.state.pending_keys));
+            let keys = std::mem::take(&mut self.state.pending_keys);
+            self.state.pending_keys_time = None;
+
+            if self.context().is_text_input() {
+                for key in keys {
+                    self.input_text(key);
+                }
+            }
+        }
+    }
+
+    /// Handle a mouse event: the wheel scrolls the list and a click moves the cursor to the clicked row,
+    /// clicking the highlighted row toggles it
+    pub fn mouse_event(&mut self, mouse_event: MouseEvent) {
+        let state = self.state_mut();
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => state.active_list_mut().backward(MOUSE_SCROLL_STEP),
+            MouseEventKind::ScrollDown => state.active_list_mut().forward(MOUSE_SCROLL_STEP),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let area = state.list_area;
+                let inside_list = mouse_event.column > area.left()
+                    && mouse_event.column < area.right().saturating_sub(1)
+                    && mouse_event.row > area.top()
+                    && mouse_event.row < area.bottom().saturating_sub(1);
+                if !inside_list {
+                    return;
+                }
+
+                let list = state.active_list_mut();
+                let position = list.list_state().offset() + (mouse_event.row - area.top() - 1) as usize;
+                if position >= list.len() {
+                    return;
+                }
+
+                if list.list_state().selected() == Some(position) {
+                    state.toggle_row(position);
+                    state.selection_anchor = Some(position);
+                } else {
+                    list.list_state_mut().select(Some(position));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Move the cursor with `step` and select every entry between the anchor and the new cursor position
+    fn extend_selection<F>(&mut self, step: F)
+    where
+        F: Fn(usize, usize) -> usize,
+    {
+        let state = self.state_mut();
+        let len = state.active_list().len();
+        let Some(current_position) = state.active_list().list_state().selected() else { return; };
+
+        let anchor = *state.selection_anchor.get_or_insert(current_position);
+        let new_position = step(current_position, len);
+        state.active_list_mut().list_state_mut().select(Some(new_position));
+
+        for position in anchor.min(new_position)..=anchor.max(new_position) {
+            for idx in state.row_entries(position) {
+                state.set_row_entry_selected(idx, true);
+            }
+        }
+    }
+
+    /// Add to the selection every entry whose path matches the given pattern
+    pub fn select_matching(&mut self, pattern: &str) {
+        let regex = match regex::bytes::Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.state.toasts.error(format!("Invalid selection pattern {}: {}", pattern, e));
+                return;
+            }
+        };
+
+        let state = self.state_mut();
+        let matching_paths = state.entries.items
+            .iter()
+            .zip(state.entries_path.iter())
+            .filter(|(entry, _)| regex.is_match(&os_str_bytes(entry.path().as_os_str())))
+            .map(|(_, path)| path.clone())
+            .collect::<Vec<_>>();
+
+        state.toasts.info(format!("Pattern {} selects {} entries", pattern, matching_paths.len()));
+        state.selected_entries.extend(matching_paths);
+    }
+
+    /// Narrow the list to the entries fuzzy matching the search pattern
+    pub fn update_search(&mut self) {
+        let state = self.state_mut();
+        let pattern = state.search_pattern().to_string();
+
+        state.search_hit_cursor = 0;
+        state.selection_anchor = None;
+
+        if pattern.is_empty() {
+            state.search_hits.clear();
+            state.search_highlights.clear();
+            state.entries.set_visible(None);
+            state.update_tree();
+            return;
+        }
+
+        let matcher = SkimMatcherV2::default();
+        let mut scored_hits = (0..state.entries.items.len())
+            .filter_map(|idx| {
+                matcher
+                    .fuzzy_indices(&state.display_path(idx), &pattern)
+                    .map(|(score, indices)| (idx, score, indices))
+            })
+            .collect::<Vec<_>>();
+
+        // the list stays sorted by size, hits are visited from the best score
+        let visible = scored_hits.iter().map(|(idx, _, _)| *idx).collect::<Vec<_>>();
+        scored_hits.sort_by(|(_, a, _), (_, b, _)| b.cmp(a));
+
+        state.search_hits = scored_hits.iter().map(|(idx, _, _)| *idx).collect();
+        state.search_highlights = scored_hits
+            .into_iter()
+            .map(|(idx, _, indices)| (idx, indices))
+            .collect();
+        state.entries.set_visible(Some(visible));
+        state.update_tree();
+    }
+
+    /// Move the cursor `offset` hits away from the last visited one, by decreasing score
+    fn jump_to_hit(&mut self, offset: isize) {
+        let state = self.state_mut();
+        if state.search_hits.is_empty() {
+            return;
+        }
+
+        let hits_count = state.search_hits.len() as isize;
+        state.search_hit_cursor = (state.search_hit_cursor as isize + offset).rem_euclid(hits_count) as usize;
+        let hit = state.search_hits[state.search_hit_cursor];
+        state.select_entry(hit);
+    }
+
     pub fn scan_dir_update(&mut self) {
         let state = self.state_mut();
-        let regex_fiter = regex::Regex::new(&state.regex_filter).unwrap();
+        let regex_fiter = match regex::bytes::Regex::new(&state.regex_filter) {
+            Ok(regex_fiter) => regex_fiter,
+            Err(e) => {
+                state.toasts.error(format!("Invalid filter {}: {}", state.regex_filter, e));
+                return;
+            }
+        };
 
-        let mut dir_entries = get_dir_list_from_path(
-            &state.path,
-            &|entry| {
-                regex_fiter.is_match(entry.to_str().unwrap())
+        let filter_filename = |file_name: &std::ffi::OsStr| regex_fiter.is_match(&os_str_bytes(file_name));
+        let excluded_dirs = state.excluded_dirs();
+        let mut dir_entries = vec![];
+        let mut entries_root = vec![];
+        for (root_idx, root) in state.roots.iter().enumerate() {
+            let root_entries = get_dir_list_from_path(root, &filter_filename, state.prune, &excluded_dirs);
+            for entry in root_entries {
+                dir_entries.push(entry);
+                entries_root.push(root_idx);
             }
-            )
-        .collect::<Vec<_>>();
+        }
 
-        let mut entries_size = dir_entries
+        let mut entries_content = dir_entries
             .iter()
-            .map(|entry| count_and_size(entry.path()).1)
+            .map(|entry| measure_content(entry.path()))
             .collect::<Vec<_>>();
 
         // compute permutation sorted by size
-        let mut permutation = (0..entries_size.len()).collect::<Vec<usize>>();
-        permutation.sort_by(|a, b| entries_size[*b].cmp(&entries_size[*a]));
+        let mut permutation = (0..entries_content.len()).collect::<Vec<usize>>();
+        permutation.sort_by(|a, b| entries_content[*b].size.cmp(&entries_content[*a].size));
 
         // apply permutation
         dir_entries = permutation.iter().map(|&idx| dir_entries[idx].clone()).collect();
-        entries_size = permutation.iter().map(|&idx| entries_size[idx]).collect();
+        entries_content = permutation.iter().map(|&idx| std::mem::take(&mut entries_content[idx])).collect();
+        entries_root = permutation.iter().map(|&idx| entries_root[idx]).collect();
+
+        let entries_path = dir_entries
+            .iter()
+            .map(|entry| canonicalize_parent(entry.path()).unwrap_or_else(|_| entry.path().to_path_buf()))
+            .collect::<Vec<_>>();
+
+        // keep the cursor on the same entry if it is still listed
+        let current_path = state.entries.selected_index().map(|idx| state.entries_path[idx].clone());
 
         state.entries.set_items(dir_entries);
-        state.entries_size = entries_size;
-        state.selected_entries_idx.clear();
+        state.entries_size = entries_content.iter().map(|content| content.size).collect();
+        state.entries_file_count = entries_content.iter().map(|content| content.file_count).collect();
+        state.entries_git_dir = entries_content.into_iter().map(|content| content.git_dir).collect();
+        state.entries_root = entries_root;
+        state.selection_anchor = None;
+
+        if let Some(idx) = current_path.and_then(|path| entries_path.iter().position(|p| *p == path)) {
+            state.entries.state.select(Some(idx));
+        }
+
+        // drop selected paths that are no longer part of the list
+        let listed_paths = entries_path.iter().collect::<HashSet<_>>();
+        let (kept, dropped): (HashSet<_>, HashSet<_>) = state.selected_entries
+            .drain()
+            .partition(|path| listed_paths.contains(path));
+        state.selected_entries = kept;
+        state.dropped_selection = dropped.into_iter().collect();
+        state.entries_path = entries_path;
+        // the content of the entries may have changed
+        state.reset_preview();
+
+        if !state.dropped_selection.is_empty() {
+            debug!("Unselected entries: {:?}", state.dropped_selection);
+            state.toasts.warn(format!("{} selected entries are no longer listed and were unselected", state.dropped_selection.len()));
+        }
+
+        self.update_search();
+    }
+
+    /// What currently receives the keys
+    pub fn context(&self) -> ActionContext {
+        let state = self.state();
+        if state.search_text_area.focused {
+            ActionContext::Search
+        } else if state.deletions_view.is_some() {
+            ActionContext::Deletions
+        } else if state.search_picker.is_some() {
+            ActionContext::SearchPicker
+        } else if state.save_search_text_area.focused {
+            ActionContext::SaveSearch
+        } else if state.select_text_area.focused {
+            ActionContext::SelectPattern
+        } else if state.path_text_area.focused {
+            ActionContext::EditPath
+        } else if state.filter_text_area.focused {
+            ActionContext::EditFilter
+        } else {
+            match (state.mode, state.view_mode) {
+                (AppMode::Browse, _) => ActionContext::Browse,
+                (AppMode::Filter, ViewMode::List) => ActionContext::List,
+                (AppMode::Filter, ViewMode::Tree) => ActionContext::Tree,
+            }
+        }
     }
 
+    /// Actions of the current context
     pub fn actions(&self) -> &Actions {
-        &self.actions
+        &self.actions[&self.context()]
     }
     pub fn state(&self) -> &AppState {
         &self.state
@@ -237,3 +1488,14 @@ impl App {
     }
 
 }
+
+/// Summary of a successful deletion
+fn deletion_message(count: usize, size: u64, strategy: DeletionStrategy) -> String {
+    let verb = match strategy {
+        DeletionStrategy::Trash => "Moved to the trash",
+        DeletionStrategy::Permanent => "Deleted",
+        DeletionStrategy::Quarantine => "Moved to the quarantine",
+    };
+    let undo_hint = if strategy == DeletionStrategy::Permanent { "" } else { " (u to undo)" };
+    format!("{} {} entries, {:.2}MB freed{}", verb, count, size as f32 / 1000000.0, undo_hint)
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use walkdir::WalkDir;
+
+/// Number of largest files kept in the preview
+const LARGEST_FILES_COUNT: usize = 5;
+
+/// Top-level child of a previewed directory
+#[derive(Clone, Debug)]
+pub struct PreviewChild {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Files sharing the same extension
+#[derive(Clone, Debug)]
+pub struct ExtensionStats {
+    pub extension: String,
+    pub file_count: usize,
+    pub size: u64,
+}
+
+/// Summary of the content of an entry, displayed in the preview panel
+#[derive(Clone, Debug)]
+pub struct Preview {
+    pub path: PathBuf,
+    pub file_count: usize,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// Sorted by decreasing size
+    pub children: Vec<PreviewChild>,
+    /// Sorted by decreasing size
+    pub largest_files: Vec<(PathBuf, u64)>,
+    /// Sorted by decreasing size
+    pub extensions: Vec<ExtensionStats>,
+}
+
+impl Preview {
+    /// Walk the whole entry once to gather its statistics, `None` if `cancelled` is set meanwhile
+    pub fn compute(path: &Path, cancelled: &AtomicBool) -> Option<Self> {
+        let metadata = path.metadata().ok();
+
+        let mut children = HashMap::<String, PreviewChild>::new();
+        let mut extensions = HashMap::<String, ExtensionStats>::new();
+        let mut largest_files = Vec::<(PathBuf, u64)>::new();
+        let mut file_count = 0;
+        let mut size = 0;
+
+        // a matched file is its own content
+        if let Some(metadata) = metadata.as_ref().filter(|metadata| metadata.is_file()) {
+            file_count = 1;
+            size = metadata.len();
+        }
+
+        for entry in WalkDir::new(path).min_depth(1).into_iter().filter_map(|e| e.ok()) {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let relative_path = entry.path().strip_prefix(path).unwrap_or(entry.path());
+            let Some(top_level_name) = relative_path.components().next() else { continue; };
+            let top_level_name = top_level_name.as_os_str().to_string_lossy().into_owned();
+
+            let child = children.entry(top_level_name.clone()).or_insert_with(|| PreviewChild {
+                name: top_level_name,
+                is_dir: false,
+                size: 0,
+            });
+            if entry.depth() == 1 {
+                child.is_dir = entry.file_type().is_dir();
+            }
+
+            let Some(metadata) = entry.metadata().ok().filter(|metadata| metadata.is_file()) else { continue; };
+            let file_size = metadata.len();
+            child.size += file_size;
+            file_count += 1;
+            size += file_size;
+
+            let extension = entry.path()
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("(none)"));
+            let stats = extensions.entry(extension.clone()).or_insert_with(|| ExtensionStats {
+                extension,
+                file_count: 0,
+                size: 0,
+            });
+            stats.file_count += 1;
+            stats.size += file_size;
+
+            largest_files.push((relative_path.to_path_buf(), file_size));
+            if largest_files.len() > LARGEST_FILES_COUNT {
+                largest_files.sort_by(|(_, a), (_, b)| b.cmp(a));
+                largest_files.truncate(LARGEST_FILES_COUNT);
+            }
+        }
+
+        let mut children = children.into_values().collect::<Vec<_>>();
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+        let mut extensions = extensions.into_values().collect::<Vec<_>>();
+        extensions.sort_by_key(|stats| std::cmp::Reverse(stats.size));
+        largest_files.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        Some(Self {
+            path: path.to_path_buf(),
+            file_count,
+            size,
+            modified: metadata.as_ref().and_then(|metadata| metadata.modified().ok()),
+            created: metadata.as_ref().and_then(|metadata| metadata.created().ok()),
+            children,
+            largest_files,
+            extensions,
+        })
+    }
+}
+
+/// Preview computed on a worker thread, so that walking a large entry does not block the interface
+#[derive(Clone, Debug)]
+pub struct PreviewTask {
+    pub path: PathBuf,
+    result: Arc<Mutex<Option<Preview>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl PreviewTask {
+    pub fn spawn(path: PathBuf) -> Self {
+        let task = Self { path, result: Arc::default(), cancelled: Arc::default() };
+
+        let (path, result, cancelled) = (task.path.clone(), task.result.clone(), task.cancelled.clone());
+        std::thread::spawn(move || {
+            if let Some(preview) = Preview::compute(&path, &cancelled) {
+                *result.lock().unwrap_or_else(|e| e.into_inner()) = Some(preview);
+            }
+        });
+
+        task
+    }
+
+    /// The preview once computed
+    pub fn take_result(&self) -> Option<Preview> {
+        self.result.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Stop the walk, its preview is no longer needed
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use eyre::{eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigLayer, RootPaths};
+use crate::utils::walker::DeletionStrategy;
+
+/// Roots, filter and options of a search, saved under a name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SavedSearch {
+    pub roots: Vec<PathBuf>,
+    pub regex_filter: String,
+    pub prune: bool,
+    pub deletion_strategy: DeletionStrategy,
+}
+
+impl SavedSearch {
+    /// Configuration layer setting the values of the search
+    pub fn to_layer(&self) -> ConfigLayer {
+        ConfigLayer {
+            root_path: Some(RootPaths::Many(self.roots.clone())),
+            regex_filter: Some(self.regex_filter.clone()),
+            prune: Some(self.prune),
+            deletion_strategy: Some(self.deletion_strategy),
+            ..Default::default()
+        }
+    }
+}
+
+/// Saved searches by name, persisted as TOML in the state directory
+#[derive(Debug, Clone, Default)]
+pub struct SavedSearches {
+    path: PathBuf,
+    searches: BTreeMap<String, SavedSearch>,
+    /// Set when the file could not be loaded, so that it is not overwritten
+    read_only: bool,
+}
+
+impl SavedSearches {
+    /// `searches.toml` in `state_dir`
+    pub fn path(state_dir: &Path) -> PathBuf {
+        state_dir.join("searches.toml")
+    }
+
+    /// Load the searches saved in `state_dir`, none if the file does not exist yet
+    pub fn load(state_dir: &Path) -> Result<Self> {
+        let path = Self::path(state_dir);
+        let searches = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .wrap_err_with(|| format!("Unable to read the saved searches {}", path.display()))?;
+            toml::from_str(&content)
+                .wrap_err_with(|| format!("Invalid saved searches file {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { path, searches, read_only: false })
+    }
+
+    /// No searches, the file of `state_dir` being left untouched, e.g. when it cannot be loaded
+    pub fn read_only(state_dir: &Path) -> Self {
+        Self { path: Self::path(state_dir), searches: BTreeMap::new(), read_only: true }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.get(name)
+    }
+
+    /// Names of the searches, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.searches.keys().cloned().collect()
+    }
+
+    /// Save `search` under `name`, replacing a search of the same name
+    pub fn insert(&mut self, name: impl Into<String>, search: SavedSearch) -> Result<()> {
+        self.searches.insert(name.into(), search);
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        self.searches.remove(name);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.read_only {
+            return Err(eyre!("Not overwriting the saved searches {}, which could not be loaded", self.path.display()));
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .wrap_err_with(|| format!("Unable to create the state directory {}", parent.display()))?;
+        }
+        let content = toml::to_string(&self.searches).wrap_err("Unable to serialize the saved searches")?;
+        std::fs::write(&self.path, content)
+            .wrap_err_with(|| format!("Unable to write the saved searches {}", self.path.display()))
+    }
+}
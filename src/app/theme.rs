@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use eyre::{Result, eyre};
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "high-contrast", "monochrome"];
+
+/// Style of every element of the interface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Totals displayed above the list
+    pub infos: Style,
+    pub selected: Style,
+    /// Tree node whose entries are only partly selected
+    pub partially_selected: Style,
+    /// Characters matched by the search
+    pub search_match: Style,
+    /// Row under the cursor
+    pub highlight: Style,
+    /// Section titles of the preview
+    pub title: Style,
+    pub help_key: Style,
+    pub help_text: Style,
+    pub focused_input: Style,
+    pub unfocused_input: Style,
+    /// Unfinished key sequence
+    pub pending_keys: Style,
+    /// Marker of the names which are not valid UTF-8
+    pub non_utf8: Style,
+    pub status_bar: Style,
+    pub toast_info: Style,
+    pub toast_warn: Style,
+    pub toast_error: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        let orange = Color::Rgb(255, 165, 0);
+        Self {
+            infos: Style::default().fg(Color::LightCyan),
+            selected: Style::default().fg(orange),
+            partially_selected: Style::default().fg(orange).add_modifier(Modifier::DIM),
+            search_match: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            highlight: Style::default(),
+            title: Style::default().fg(Color::LightCyan).add_modifier(Modifier::BOLD),
+            help_key: Style::default().fg(Color::LightCyan),
+            help_text: Style::default().fg(Color::Gray),
+            focused_input: Style::default().fg(Color::LightGreen),
+            unfocused_input: Style::default().fg(Color::DarkGray),
+            pending_keys: Style::default().add_modifier(Modifier::BOLD),
+            non_utf8: Style::default().fg(Color::LightRed).add_modifier(Modifier::ITALIC),
+            status_bar: Style::default().fg(Color::Black).bg(Color::Gray),
+            toast_info: Style::default().fg(Color::LightGreen),
+            toast_warn: Style::default().fg(Color::LightYellow),
+            toast_error: Style::default().fg(Color::LightRed),
+        }
+    }
+
+    pub fn light() -> Self {
+        let brown = Color::Rgb(175, 80, 0);
+        Self {
+            infos: Style::default().fg(Color::Blue),
+            selected: Style::default().fg(brown).add_modifier(Modifier::BOLD),
+            partially_selected: Style::default().fg(brown),
+            search_match: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            highlight: Style::default(),
+            title: Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            help_key: Style::default().fg(Color::Blue),
+            help_text: Style::default().fg(Color::Black),
+            focused_input: Style::default().fg(Color::Green),
+            unfocused_input: Style::default().fg(Color::Gray),
+            pending_keys: Style::default().add_modifier(Modifier::BOLD),
+            non_utf8: Style::default().fg(Color::Red).add_modifier(Modifier::ITALIC),
+            status_bar: Style::default().fg(Color::White).bg(Color::Blue),
+            toast_info: Style::default().fg(Color::Green),
+            toast_warn: Style::default().fg(Color::Rgb(175, 95, 0)),
+            toast_error: Style::default().fg(Color::Red),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            infos: Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            selected: Style::default().fg(Color::Black).bg(Color::Yellow),
+            partially_selected: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            search_match: Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            highlight: Style::default().add_modifier(Modifier::REVERSED),
+            title: Style::default().fg(Color::White).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            help_key: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            help_text: Style::default().fg(Color::White),
+            focused_input: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            unfocused_input: Style::default().fg(Color::White),
+            pending_keys: Style::default().fg(Color::Black).bg(Color::White),
+            non_utf8: Style::default().fg(Color::White).bg(Color::Red),
+            status_bar: Style::default().fg(Color::Black).bg(Color::White),
+            toast_info: Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            toast_warn: Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            toast_error: Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Modifiers only, used when `NO_COLOR` is set
+    pub fn monochrome() -> Self {
+        Self {
+            infos: Style::default(),
+            selected: Style::default().add_modifier(Modifier::BOLD),
+            partially_selected: Style::default().add_modifier(Modifier::DIM),
+            search_match: Style::default().add_modifier(Modifier::UNDERLINED),
+            highlight: Style::default().add_modifier(Modifier::REVERSED),
+            title: Style::default().add_modifier(Modifier::BOLD),
+            help_key: Style::default().add_modifier(Modifier::BOLD),
+            help_text: Style::default(),
+            focused_input: Style::default().add_modifier(Modifier::BOLD),
+            unfocused_input: Style::default().add_modifier(Modifier::DIM),
+            pending_keys: Style::default().add_modifier(Modifier::REVERSED),
+            non_utf8: Style::default().add_modifier(Modifier::ITALIC),
+            status_bar: Style::default().add_modifier(Modifier::REVERSED),
+            toast_info: Style::default(),
+            toast_warn: Style::default().add_modifier(Modifier::BOLD),
+            toast_error: Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    fn style_mut(&mut self, element: &str) -> Option<&mut Style> {
+        let style = match element {
+            "infos" => &mut self.infos,
+            "selected" => &mut self.selected,
+            "partially_selected" => &mut self.partially_selected,
+            "search_match" => &mut self.search_match,
+            "highlight" => &mut self.highlight,
+            "title" => &mut self.title,
+            "help_key" => &mut self.help_key,
+            "help_text" => &mut self.help_text,
+            "focused_input" => &mut self.focused_input,
+            "unfocused_input" => &mut self.unfocused_input,
+            "pending_keys" => &mut self.pending_keys,
+            "non_utf8" => &mut self.non_utf8,
+            "status_bar" => &mut self.status_bar,
+            "toast_info" => &mut self.toast_info,
+            "toast_warn" => &mut self.toast_warn,
+            "toast_error" => &mut self.toast_error,
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    /// Find a theme by name, the themes of the configuration shadowing the built-in ones
+    pub fn resolve(name: &str, definitions: &BTreeMap<String, ThemeDefinition>) -> Result<Self> {
+        Self::resolve_chain(name, definitions, &mut vec![])
+    }
+
+    /// `chain` holds the themes being resolved, based on each other
+    fn resolve_chain<'a>(name: &'a str, definitions: &'a BTreeMap<String, ThemeDefinition>, chain: &mut Vec<&'a str>) -> Result<Self> {
+        // a theme of the configuration may redefine a built-in theme it is based on
+        let definition = definitions.get(name).filter(|_| !chain.contains(&name));
+        let Some(definition) = definition else {
+            return Self::builtin(name).ok_or_else(|| match chain.is_empty() {
+                true => eyre!("Unknown theme {}, expected one of {} or a theme of the configuration", name, BUILTIN_THEMES.join(", ")),
+                false if chain.contains(&name) => eyre!("Theme {} is based on itself through {}", name, chain.join(", ")),
+                false => eyre!("Unknown base theme {} of {}", name, chain.join(", ")),
+            });
+        };
+        chain.push(name);
+        let mut theme = Self::resolve_chain(definition.base.as_deref().unwrap_or("dark"), definitions, chain)?;
+
+        for (element, style_definition) in &definition.styles {
+            let style = theme
+                .style_mut(element)
+                .ok_or_else(|| eyre!("Unknown element {} in theme {}", element, name))?;
+            *style = style_definition.to_style().map_err(|e| eyre!("Invalid style {} in theme {}: {}", element, name, e))?;
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Theme of the configuration file, e.g.
+/// `[themes.mine]` with `base = "light"` and `selected = { fg = "#d75f00", modifiers = ["bold"] }`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeDefinition {
+    /// Theme providing the styles which are not redefined, `dark` by default
+    pub base: Option<String>,
+    /// Styles by element name
+    #[serde(flatten)]
+    pub styles: BTreeMap<String, StyleDefinition>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleDefinition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<String>,
+}
+
+impl StyleDefinition {
+    pub fn to_style(&self) -> Result<Style, String> {
+        let mut style = Style::default();
+        if let Some(fg) = &self.fg {
+            style = style.fg(parse_color(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(parse_color(bg)?);
+        }
+        for modifier in &self.modifiers {
+            style = style.add_modifier(parse_modifier(modifier)?);
+        }
+        Ok(style)
+    }
+}
+
+/// Parse a color name (`lightcyan`), a 256 colors index (`208`) or a RGB hexadecimal color (`#ffa500`)
+fn parse_color(s: &str) -> Result<Color, String> {
+    if let Some(hex) = s.strip_prefix('#') {
+        let rgb = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6).ok_or_else(|| format!("invalid color {}", s))?;
+        return Ok(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+    }
+    if let Ok(index) = s.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let color = match s.to_lowercase().replace(['-', '_'], "").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("unknown color {}", s)),
+    };
+    Ok(color)
+}
+
+fn parse_modifier(s: &str) -> Result<Modifier, String> {
+    let modifier = match s.to_lowercase().as_str() {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underlined" => Modifier::UNDERLINED,
+        "slow_blink" => Modifier::SLOW_BLINK,
+        "rapid_blink" => Modifier::RAPID_BLINK,
+        "reversed" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "crossed_out" => Modifier::CROSSED_OUT,
+        _ => return Err(format!("unknown modifier {}", s)),
+    };
+    Ok(modifier)
+}
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use strum_macros::Display as StrumDisplay;
+
+/// Maximum number of toasts kept on screen, the oldest being dropped first
+const MAX_TOASTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, StrumDisplay)]
+pub enum ToastLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl ToastLevel {
+    /// How long a toast of this level stays displayed
+    fn timeout(&self) -> Duration {
+        match self {
+            ToastLevel::Info => Duration::from_secs(3),
+            ToastLevel::Warn => Duration::from_secs(5),
+            ToastLevel::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// Transient notification displayed over the interface
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub message: String,
+    posted: Instant,
+}
+
+/// Notifications posted by the application operations, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct Toasts(VecDeque<Toast>);
+
+impl Toasts {
+    /// Display a notification, also written to the log
+    pub fn post(&mut self, level: ToastLevel, message: impl Into<String>) {
+        let message = message.into();
+        match level {
+            ToastLevel::Info => log::info!("{}", message),
+            ToastLevel::Warn => log::warn!("{}", message),
+            ToastLevel::Error => log::error!("{}", message),
+        }
+
+        self.0.push_back(Toast { level, message, posted: Instant::now() });
+        if self.0.len() > MAX_TOASTS {
+            self.0.pop_front();
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.post(ToastLevel::Info, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.post(ToastLevel::Warn, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.post(ToastLevel::Error, message);
+    }
+
+    /// Remove the toasts displayed for longer than their timeout
+    pub fn expire(&mut self) {
+        self.0.retain(|toast| toast.posted.elapsed() < toast.level.timeout());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::utils::os_path::is_lossy;
+
+/// A displayed row of the tree view
+#[derive(Clone, Debug)]
+pub struct TreeRow {
+    pub depth: usize,
+    /// Name of the node, single child directories are compacted as `a/b/c`
+    pub label: String,
+    /// Whether the label replaces names which are not valid UTF-8
+    pub lossy_label: bool,
+    /// Full path of the node, used as a key to remember collapsed nodes
+    pub path: PathBuf,
+    /// Index of the entry displayed by this row if it is a matched entry
+    pub entry: Option<usize>,
+    /// Indices of every entry of the subtree, including the row entry itself
+    pub entries: Vec<usize>,
+    /// Aggregated size of the subtree
+    pub size: u64,
+    pub has_children: bool,
+    pub collapsed: bool,
+}
+
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<OsString, Node>,
+    entry: Option<usize>,
+}
+
+impl Node {
+    /// Size of an entry already accounts for the entries it contains
+    fn size(&self, entries_size: &[u64]) -> u64 {
+        self.entry.map_or_else(
+            || self.children.values().map(|child| child.size(entries_size)).sum(),
+            |idx| entries_size[idx],
+        )
+    }
+
+    fn collect_entries(&self, entries: &mut Vec<usize>) {
+        entries.extend(self.entry);
+        for child in self.children.values() {
+            child.collect_entries(entries);
+        }
+    }
+}
+
+/// Group the given entries under their ancestor directories relative to `root`
+/// and flatten the expanded nodes into rows, children sorted by size
+pub fn build_tree_rows(
+    root: &Path,
+    entries_path: &[PathBuf],
+    entries_size: &[u64],
+    visible_entries: impl Iterator<Item = usize>,
+    collapsed: &HashSet<PathBuf>,
+) -> Vec<TreeRow> {
+    let mut tree = Node::default();
+    for idx in visible_entries {
+        let path = &entries_path[idx];
+        let relative_path = path.strip_prefix(root).unwrap_or(path);
+        let node = relative_path
+            .components()
+            .fold(&mut tree, |node, component| node.children.entry(component.as_os_str().to_os_string()).or_default());
+        node.entry = Some(idx);
+    }
+
+    let mut rows = vec![];
+    push_rows(&tree, root, 0, entries_size, collapsed, &mut rows);
+    rows
+}
+
+fn push_rows(
+    node: &Node,
+    path: &Path,
+    depth: usize,
+    entries_size: &[u64],
+    collapsed: &HashSet<PathBuf>,
+    rows: &mut Vec<TreeRow>,
+) {
+    let mut children = node.children
+        .iter()
+        .map(|(name, child)| (name, child, child.size(entries_size)))
+        .collect::<Vec<_>>();
+    children.sort_by(|(_, _, a), (_, _, b)| b.cmp(a));
+
+    for (name, mut child, size) in children {
+        let mut label = name.to_string_lossy().into_owned();
+        let mut lossy_label = is_lossy(name);
+        let mut child_path = path.join(name);
+
+        // compact chains of directories holding a single child
+        while child.entry.is_none() && child.children.len() == 1 {
+            let (grand_child_name, grand_child) = child.children.iter().next().unwrap();
+            label.push('/');
+            label.push_str(&grand_child_name.to_string_lossy());
+            lossy_label |= is_lossy(grand_child_name);
+            child_path.push(grand_child_name);
+            child = grand_child;
+        }
+
+        let mut entries = vec![];
+        child.collect_entries(&mut entries);
+        let is_collapsed = collapsed.contains(&child_path);
+
+        rows.push(TreeRow {
+            depth,
+            label,
+            lossy_label,
+            path: child_path.clone(),
+            entry: child.entry,
+            entries,
+            size,
+            has_children: !child.children.is_empty(),
+            collapsed: is_collapsed,
+        });
+
+        if !is_collapsed {
+            push_rows(child, &child_path, depth + 1, entries_size, collapsed, rows);
+        }
+    }
+}
@@ -1,16 +1,35 @@
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect, Alignment},
-    style::{Color, Style},
+    style::Style,
     widgets::*,
     text::{Span, Line},
     Frame,
 };
 
-use eyre::{Result, eyre};
 
-use super::actions::Actions;
-use crate::{app::{App, AppState}, utils::key_display::KeyEventWrapper};
+use super::{actions::Actions, preview::Preview, theme::Theme, toasts::ToastLevel};
+use crate::{app::{App, AppMode, AppState, ViewMode}, logger, utils::key_display::KeySequenceWrapper};
+use log::Level;
+use crate::utils::os_path::{is_lossy, tilde_path};
+use crate::utils::walker::DeletionStrategy;
+
+/// Minimum width of the entry list for the preview panel to be displayed on its right
+const PREVIEW_SIDE_MIN_WIDTH: u16 = 100;
+/// Height of the log panel, borders included, at most a third of the body
+const LOG_PANEL_HEIGHT: u16 = 10;
+/// Below this size only a placeholder is drawn
+const MIN_WIDTH: u16 = 24;
+const MIN_HEIGHT: u16 = 8;
+/// Below this width the help panel is hidden
+const HELP_PANEL_MIN_WIDTH: u16 = 80;
+const HELP_PANEL_WIDTH: u16 = 34;
+/// Below this height the path and filter inputs share a row and the infos fit on a single line
+const COMPACT_MAX_HEIGHT: u16 = 24;
+/// Toasts wrap their message beyond this width
+const TOAST_MAX_WIDTH: u16 = 60;
+/// Width of the saved search picker and of the deletions view, borders included
+const POPUP_WIDTH: u16 = 70;
 
 pub fn draw<B>(frame: &mut Frame<B>, app: &mut App)
 where
@@ -18,104 +37,553 @@ where
 {
     let size = frame.size();
 
-    // Body & Help
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        draw_too_small(frame, size);
+        return;
+    }
+    let compact = size.height < COMPACT_MAX_HEIGHT;
+
+    // Body & Help above the status bar, the help is dropped on narrow terminals
+    let screen_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(size);
+    let help_width = if size.width >= HELP_PANEL_MIN_WIDTH { HELP_PANEL_WIDTH } else { 0 };
     let body_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(20), Constraint::Length(34)])
-        .split(size);
+        .constraints([Constraint::Min(20), Constraint::Length(help_width)])
+        .split(screen_chunks[0]);
+
+    // The selection pattern and search name inputs are only shown while they are being edited
+    let select_height = if app.state.select_text_area.focused || app.state.save_search_text_area.focused { 3 } else { 0 };
+    // The search box stays visible as long as the list is narrowed
+    let search_height = if app.state.search_text_area.focused || !app.state.search_pattern().is_empty() { 3 } else { 0 };
+    let logs_height = if app.state.show_logs { LOG_PANEL_HEIGHT.min(body_chunks[0].height / 3) } else { 0 };
+
+    // infos, one line per root when several are scanned
+    let infos = match app.state.mode {
+        AppMode::Filter => app_infos(app.state()),
+        AppMode::Browse => browse_infos(app.state()),
+    };
+    let infos = if compact {
+        vec![Line::from(infos.join(" | "))]
+    } else {
+        infos.into_iter().map(Line::from).collect::<Vec<_>>()
+    };
+    let infos_height = (infos.len() as u16).min(body_chunks[0].height / 4).max(1);
 
     let content_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(2), Constraint::Min(3)])
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(if compact { 0 } else { 3 }),
+            Constraint::Length(infos_height),
+            Constraint::Length(select_height),
+            Constraint::Length(search_height),
+            Constraint::Min(3),
+            Constraint::Length(logs_height),
+        ])
         .split(body_chunks[0]);
 
-    let help = draw_help(app.actions());
-    frame.render_widget(help, body_chunks[1]);
+    if help_width > 0 {
+        let help = draw_help(app.actions(), &app.context().to_string(), &app.state.theme);
+        frame.render_widget(help, body_chunks[1]);
+    }
+
+    // text areas, side by side on short terminals
+    let (path_area, filter_area) = if compact {
+        let input_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(content_chunks[0]);
+        (input_chunks[0], input_chunks[1])
+    } else {
+        (content_chunks[0], content_chunks[1])
+    };
+    frame.render_widget(app.state.path_text_area.widget(), path_area);
+    frame.render_widget(app.state.filter_text_area.widget(), filter_area);
+
+    frame.render_widget(Paragraph::new(infos).style(app.state.theme.infos), content_chunks[2]);
+
+    draw_status_bar(frame, app, screen_chunks[1]);
+
+    if app.state.show_logs {
+        frame.render_widget(draw_logs(&app.state.theme, content_chunks[6].height.saturating_sub(2)), content_chunks[6]);
+    }
+
+    if app.state.select_text_area.focused {
+        frame.render_widget(app.state.select_text_area.widget(), content_chunks[3]);
+    } else if app.state.save_search_text_area.focused {
+        frame.render_widget(app.state.save_search_text_area.widget(), content_chunks[3]);
+    }
 
-    // text areas
-    frame.render_widget(app.state.path_text_area.widget(), content_chunks[0]);
-    frame.render_widget(app.state.filter_text_area.widget(), content_chunks[1]);
+    if search_height > 0 {
+        frame.render_widget(app.state.search_text_area.widget(), content_chunks[4]);
+    }
 
-    // infos
-    frame.render_widget(app_infos(app.state()), content_chunks[2]);
+    // The preview goes on the right of the list, or below it when the list is narrow
+    let list_area = if app.state.show_preview {
+        let preview_direction = if content_chunks[5].width >= PREVIEW_SIDE_MIN_WIDTH { Direction::Horizontal } else { Direction::Vertical };
+        let list_chunks = Layout::default()
+            .direction(preview_direction)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(content_chunks[5]);
 
-    let (content_list, content_list_state) = content(app.state_mut());
-    frame.render_stateful_widget(content_list, content_chunks[3], content_list_state);
+        app.state.update_preview();
+        frame.render_widget(draw_preview(app.state.preview.as_ref(), app.state.preview_task.is_some(), &app.state.theme), list_chunks[1]);
+        list_chunks[0]
+    } else {
+        content_chunks[5]
+    };
+
+    app.state.list_area = list_area;
+    let (content_list, content_list_state) = match (app.state.mode, app.state.view_mode) {
+        (AppMode::Browse, _) => browse_content(app.state_mut()),
+        (AppMode::Filter, ViewMode::List) => content(app.state_mut()),
+        (AppMode::Filter, ViewMode::Tree) => tree_content(app.state_mut()),
+    };
+    frame.render_stateful_widget(content_list, list_area, content_list_state);
 
     frame.render_stateful_widget(
         Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("↑"))
             .end_symbol(Some("↓")),
-            content_chunks[3],
-        &mut app.state.entries.get_scrollbar_state(),
+            list_area,
+        &mut app.state.active_list().get_scrollbar_state(),
     );
+
+    if app.state.search_picker.is_some() {
+        draw_search_picker(frame, app.state_mut(), content_chunks[5]);
+    }
+    if app.state.deletions_view.is_some() {
+        draw_deletions(frame, app.state_mut(), content_chunks[5]);
+    }
+
+    // toasts go last, over everything else
+    draw_toasts(frame, app.state(), screen_chunks[0]);
+}
+
+/// Popup listing the recent deletion batches over the top of `area`, most recent first
+fn draw_deletions<B>(frame: &mut Frame<B>, state: &mut AppState, area: Rect)
+where
+    B: Backend,
+{
+    let Some(view) = &mut state.deletions_view else { return; };
+    let width = POPUP_WIDTH.min(area.width);
+    let height = (view.items.len().max(1) as u16 + 2).min(area.height);
+    let view_area = Rect::new(area.x + (area.width - width) / 2, area.y, width, height);
+
+    let items = if view.items.is_empty() {
+        vec![ListItem::new("Nothing deleted yet")]
+    } else {
+        view.items
+            .iter()
+            .map(|batch_idx| {
+                let batch = &state.deletions.batches()[*batch_idx];
+                let status = match (batch.strategy, batch.restored) {
+                    (_, true) => "restored",
+                    (DeletionStrategy::Trash, false) => "in the trash",
+                    (DeletionStrategy::Permanent, false) => "deleted",
+                    (DeletionStrategy::Quarantine, false) => "in quarantine",
+                };
+                let first_path = batch.paths.first().map_or_else(String::new, |path| tilde_path(path, state.home_dir.as_deref()));
+                let others = match batch.paths.len() {
+                    0 | 1 => String::new(),
+                    count => format!(" and {} more", count - 1),
+                };
+                let item = ListItem::new(format!(
+                    "{} {:.2}MB {}: {}{}",
+                    batch.time.format("%H:%M:%S"),
+                    batch.size as f32 / 1000000.0,
+                    status,
+                    first_path,
+                    others,
+                ));
+                if batch.is_restorable() { item } else { item.style(state.theme.infos) }
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Recent deletions (u to restore, Esc to close)"))
+        .highlight_style(state.theme.highlight)
+        .highlight_symbol(">> ");
+    frame.render_widget(Clear, view_area);
+    frame.render_stateful_widget(list, view_area, &mut view.state);
 }
 
-pub fn check_size(rect: &Rect) -> Result<()> {
-    if rect.width < 52 {
-        return Err(eyre!("Require width >= 52, (got {})", rect.width));
+/// Popup listing the saved searches over the top of `area`
+fn draw_search_picker<B>(frame: &mut Frame<B>, state: &mut AppState, area: Rect)
+where
+    B: Backend,
+{
+    let Some(picker) = &mut state.search_picker else { return; };
+    let width = POPUP_WIDTH.min(area.width);
+    let height = (picker.items.len().max(1) as u16 + 2).min(area.height);
+    let picker_area = Rect::new(area.x + (area.width - width) / 2, area.y, width, height);
+
+    let items = if picker.items.is_empty() {
+        vec![ListItem::new("No saved search, shift+S saves the current one")]
+    } else {
+        picker.items
+            .iter()
+            .map(|name| {
+                let details = state.saved_searches.get(name).map_or_else(String::new, |search| format!(
+                    "  {} | {}",
+                    search.roots.iter().map(|root| tilde_path(root, state.home_dir.as_deref())).collect::<Vec<_>>().join(", "),
+                    search.regex_filter,
+                ));
+                ListItem::new(Line::from(vec![Span::raw(name.clone()), Span::styled(details, state.theme.infos)]))
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Saved searches (Enter to load, Del to delete, Esc to close)"))
+        .highlight_style(state.theme.highlight)
+        .highlight_symbol(">> ");
+    frame.render_widget(Clear, picker_area);
+    frame.render_stateful_widget(list, picker_area, &mut picker.state);
+}
+
+/// Placeholder replacing the interface until the terminal grows
+fn draw_too_small<B>(frame: &mut Frame<B>, size: Rect)
+where
+    B: Backend,
+{
+    let message = format!("Terminal too small ({}x{}), need {}x{}", size.width, size.height, MIN_WIDTH, MIN_HEIGHT);
+    let paragraph = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    // roughly centered vertically
+    let area = Rect { y: size.y + size.height / 3, height: size.height - size.height / 3, ..size };
+    frame.render_widget(paragraph, area);
+}
+
+/// Block title made of a path between a prefix and a suffix, the path being shortened to fit in `width`
+fn titled_path(prefix: &str, path: &std::path::Path, suffix: &str, width: u16, home_dir: Option<&std::path::Path>) -> String {
+    let path_width = (width as usize).saturating_sub(prefix.chars().count() + suffix.chars().count() + 2);
+    format!("{}{}{}", prefix, shorten_middle(&tilde_path(path, home_dir), path_width), suffix)
+}
+
+/// Shorten `s` to `max_width` characters by replacing its middle with `…`
+fn shorten_middle(s: &str, max_width: usize) -> String {
+    let Some((head_end, tail_start)) = middle_ellipsis(s.chars().count(), max_width) else {
+        return s.to_string();
+    };
+
+    let head = s.chars().take(head_end);
+    let tail = s.chars().skip(tail_start);
+    head.chain(std::iter::once('…')).chain(tail).collect()
+}
+
+/// Characters kept when shortening a text of `length` characters to `max_width` (at least the ellipsis):
+/// the head before `head_end` and the tail from `tail_start`, `None` if it already fits
+fn middle_ellipsis(length: usize, max_width: usize) -> Option<(usize, usize)> {
+    if length <= max_width {
+        return None;
     }
-    if rect.height < 12 {
-        return Err(eyre!("Require height >= 28, (got {})", rect.height));
+
+    let kept = max_width.saturating_sub(1);
+    Some((kept / 2, length - (kept - kept / 2)))
+}
+
+/// Context, list counters and the keys of an unfinished sequence
+fn draw_status_bar<B>(frame: &mut Frame<B>, app: &App, area: Rect)
+where
+    B: Backend,
+{
+    let state = app.state();
+    let counters = match state.mode {
+        AppMode::Filter => format!(
+            "{} entries, {} selected",
+            state.entries.visible_len(),
+            state.selected_entries.len()
+        ),
+        AppMode::Browse => format!(
+            "{} entries, {} marked",
+            state.browse.entries.items.len(),
+            state.browse.marked.len()
+        ),
+    };
+    let status = Paragraph::new(format!(" {} | {} | {}", app.context(), counters, state.deletion_strategy))
+        .style(state.theme.status_bar);
+    frame.render_widget(status, area);
+
+    if !state.pending_keys.is_empty() {
+        let pending_keys = Paragraph::new(Span::styled(
+                format!("{} … ", KeySequenceWrapper(&state.pending_keys)),
+                state.theme.pending_keys,
+            ))
+            .alignment(Alignment::Right);
+        frame.render_widget(pending_keys, area);
     }
+}
 
-    Ok(())
-}
-
-fn app_infos<'a>(state: &AppState) -> Paragraph<'a> {
-    let paragraph = {
-        let total_space =
-            state
-                .entries_size
-                .iter()
-                .sum::<u64>();
-
-        let total_selected_space =
-            state
-                .selected_entries_idx
-                .iter()
-                .map(|idx| state.entries_size[*idx])
-                .sum::<u64>();
-        
-        Paragraph::new(vec![
-            Line::from(Span::raw(format!(
-                "Total space: {:.2}MB",
-                total_space as f32 / 1000000.0
-            ))),
-            Line::from(Span::raw(format!(
-                "Total selected space: {:.2}MB ({:.2}%)",
-                total_selected_space as f32 / 1000000.0,
-                total_selected_space as f32 / total_space as f32 * 100.0
-            ))),
-        ])
+/// Stack the toasts in the bottom right corner of `area`, the most recent at the bottom
+fn draw_toasts<B>(frame: &mut Frame<B>, state: &AppState, area: Rect)
+where
+    B: Backend,
+{
+    let mut bottom = area.bottom();
+    for toast in state.toasts.iter().collect::<Vec<_>>().into_iter().rev() {
+        let style = match toast.level {
+            ToastLevel::Info => state.theme.toast_info,
+            ToastLevel::Warn => state.theme.toast_warn,
+            ToastLevel::Error => state.theme.toast_error,
+        };
+        let width = (toast.message.chars().count() as u16 + 4).min(TOAST_MAX_WIDTH).min(area.width);
+        // long messages wrap on the available width
+        let text_width = width.saturating_sub(2).max(1);
+        let height = ((toast.message.chars().count() as u16).div_ceil(text_width) + 2).min(area.height);
+        if bottom < area.top() + height {
+            break;
+        }
+        bottom -= height;
+
+        let toast_area = Rect::new(area.right() - width, bottom, width, height);
+        let paragraph = Paragraph::new(toast.message.clone())
+            .style(style)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title(toast.level.to_string()));
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(paragraph, toast_area);
+    }
+}
+
+/// Lines of the infos displayed above the list, joined on short terminals
+fn app_infos(state: &AppState) -> Vec<String> {
+    let total_space =
+        state
+            .entries_size
+            .iter()
+            .sum::<u64>();
+
+    let total_selected_space =
+        state
+            .outermost_selected_indices()
+            .map(|idx| state.entries_size[idx])
+            .sum::<u64>();
+
+    let dropped_selection = if state.dropped_selection.is_empty() {
+        String::new()
+    } else {
+        format!(", {} stale selections dropped", state.dropped_selection.len())
     };
 
-    paragraph.style(Style::default().fg(Color::LightCyan))
-    .alignment(Alignment::Left)
+    let mut infos = vec![
+        format!(
+            "Total space: {:.2}MB",
+            total_space as f32 / 1000000.0
+        ),
+        format!(
+            "Total selected space: {:.2}MB ({:.2}%){}",
+            total_selected_space as f32 / 1000000.0,
+            total_selected_space as f32 / total_space as f32 * 100.0,
+            dropped_selection
+        ),
+    ];
+
+    if state.roots.len() > 1 {
+        for (root_idx, root) in state.roots.iter().enumerate() {
+            let root_entries = (0..state.entries_root.len()).filter(|idx| state.entries_root[*idx] == root_idx);
+            let (count, space, selected_space) = root_entries.fold((0, 0, 0), |(count, space, selected_space), idx| {
+                let selected_size = if state.is_selected(idx) { state.entries_size[idx] } else { 0 };
+                (count + 1, space + state.entries_size[idx], selected_space + selected_size)
+            });
+            infos.push(format!(
+                "{}: {} entries, {:.2}MB ({:.2}MB selected)",
+                tilde_path(root, state.home_dir.as_deref()),
+                count,
+                space as f32 / 1000000.0,
+                selected_space as f32 / 1000000.0
+            ));
+        }
+    }
+
+    infos
 }
 
-fn format_item(item: (&walkdir::DirEntry, &u64)) -> String {
-    let (entry, size) = item;
-    format!(
-        "path : {}, size: {:.2}MB",
-        entry.path().display(),
-        *size as f32 / 1000000.0
-    )
+/// Width of the root column, only displayed when several roots are scanned
+const ROOT_COLUMN_MAX_WIDTH: usize = 20;
+
+fn root_column(state: &AppState, idx: usize) -> Option<String> {
+    if state.roots.len() < 2 {
+        return None;
+    }
+
+    let width = state.roots
+        .iter()
+        .map(|root| tilde_path(root, state.home_dir.as_deref()).chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(ROOT_COLUMN_MAX_WIDTH);
+    Some(format!("{:<width$} | ", shorten_middle(&state.root_label(idx), width), width = width))
+}
+
+/// Appended to the names displayed with `�` replacing invalid UTF-8 sequences
+const NON_UTF8_MARKER: &str = " [not UTF-8]";
+
+fn non_utf8_marker<'a>(lossy: bool, theme: &Theme) -> Option<Span<'a>> {
+    lossy.then(|| Span::styled(NON_UTF8_MARKER, theme.non_utf8))
+}
+
+/// Width taken in a list row by the borders and the highlight symbol
+const LIST_ROW_MARGIN: u16 = 5;
+
+fn format_item<'a>(state: &AppState, idx: usize, width: usize) -> Line<'a> {
+    let path = state.display_path(idx);
+    let marker = non_utf8_marker(is_lossy(state.entries.items[idx].path().as_os_str()), &state.theme);
+    let size = format!(", size: {:.2}MB", state.entries_size[idx] as f32 / 1000000.0);
+
+    let prefix = format!("{}path : ", root_column(state, idx).unwrap_or_default());
+    let path_width = width.saturating_sub(prefix.chars().count() + size.len() + marker.as_ref().map_or(0, |marker| marker.content.chars().count()));
+    let highlights = state.search_highlights.get(&idx);
+
+    // shorten the middle of the path, emphasizing the characters matched by the search
+    let ellipsis = middle_ellipsis(path.chars().count(), path_width);
+    let mut path_spans: Vec<Span> = vec![];
+    let mut run = String::new();
+    let mut run_highlighted = false;
+    for (char_idx, c) in path.chars().enumerate() {
+        let (c, highlighted) = match ellipsis {
+            Some((head_end, _)) if char_idx == head_end => ('…', false),
+            Some((head_end, tail_start)) if char_idx > head_end && char_idx < tail_start => continue,
+            _ => (c, highlights.is_some_and(|highlights| highlights.contains(&char_idx))),
+        };
+        if highlighted != run_highlighted && !run.is_empty() {
+            let style = if run_highlighted { state.theme.search_match } else { Style::default() };
+            path_spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_highlighted = highlighted;
+        run.push(c);
+    }
+    let style = if run_highlighted { state.theme.search_match } else { Style::default() };
+    path_spans.push(Span::styled(run, style));
+
+    let mut spans = vec![Span::raw(prefix)];
+    spans.extend(path_spans);
+    spans.extend(marker);
+    spans.push(Span::raw(size));
+    Line::from(spans)
 }
+
 fn content<'a>(state: &mut AppState) -> (List<'a>, &mut ListState) {
     (List::new(
         state.entries
+            .visible_indices()
+            .map(|idx| {
+                let item = ListItem::new(format_item(state, idx, state.list_area.width.saturating_sub(LIST_ROW_MARGIN) as usize));
+                if state.is_selected(idx) {
+                    item.style(state.theme.selected)
+                } else {
+                    item
+                }
+            })
+            .collect::<Vec<_>>())
+        .block(content_block(state))
+        .highlight_style(state.theme.highlight)
+        .highlight_symbol(">> "),
+        &mut state.entries.state)
+}
+
+fn content_block<'a>(state: &AppState) -> Block<'a> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .title(match state.roots.as_slice() {
+            [root] => titled_path("Content from path ", &root.canonicalize().unwrap_or("Unknown".into()), "", state.list_area.width, state.home_dir.as_deref()),
+            roots => format!("Content from {} roots", roots.len()),
+        })
+}
+
+fn tree_content<'a>(state: &mut AppState) -> (List<'a>, &mut ListState) {
+    (List::new(
+        state.tree
             .items
             .iter()
-            .zip(state.entries_size.iter())
-            .map(format_item)
-            .map(ListItem::new)
-            .enumerate()
-            .map(|(idx, item)| {
-                if state.selected_entries_idx.contains(&idx) {
-                    // orange
-                    item.style(Style::default().fg(Color::Rgb(255, 165, 0)))
+            .map(|row| {
+                let symbol = match (row.has_children, row.collapsed) {
+                    (false, _) => "  ",
+                    (true, true) => "▸ ",
+                    (true, false) => "▾ ",
+                };
+                let mut spans = vec![Span::raw(format!("{}{}{}", "  ".repeat(row.depth), symbol, row.label))];
+                spans.extend(non_utf8_marker(row.lossy_label, &state.theme));
+                spans.push(Span::raw(format!(
+                    " ({} entries), size: {:.2}MB",
+                    row.entries.len(),
+                    row.size as f32 / 1000000.0
+                )));
+                let item = ListItem::new(Line::from(spans));
+
+                let selected_count = row.entries.iter().filter(|idx| state.is_selected(**idx)).count();
+                if selected_count == row.entries.len() {
+                    item.style(state.theme.selected)
+                } else if selected_count > 0 {
+                    item.style(state.theme.partially_selected)
+                } else {
+                    item
+                }
+            })
+            .collect::<Vec<_>>())
+        .block(content_block(state))
+        .highlight_style(state.theme.highlight)
+        .highlight_symbol(">> "),
+        &mut state.tree.state)
+}
+
+fn browse_infos(state: &AppState) -> Vec<String> {
+    let browse = &state.browse;
+    let total_space = browse.total_size();
+    let marked_space = browse.marked_size();
+
+    vec![
+        format!(
+            "Directory space: {:.2}MB (b to go back to filter mode)",
+            total_space as f32 / 1000000.0
+        ),
+        format!(
+            "Marked: {} items, {:.2}MB",
+            browse.marked.len(),
+            marked_space as f32 / 1000000.0
+        ),
+    ]
+}
+
+/// Proportion bar of `size` relative to `total`, as displayed by ncdu
+fn size_bar(size: u64, total: u64, width: usize) -> String {
+    let filled = if total == 0 { 0 } else { (size as f64 / total as f64 * width as f64).round() as usize };
+    format!("[{}{}]", "#".repeat(filled), " ".repeat(width - filled))
+}
+
+fn browse_content<'a>(state: &mut AppState) -> (List<'a>, &mut ListState) {
+    let theme = state.theme;
+    let list_width = state.list_area.width;
+    let home_dir = state.home_dir.clone();
+    let browse = &mut state.browse;
+    browse.update();
+    let total_size = browse.total_size();
+    let title_end = if browse.task.is_some() { " (measuring...)" } else { " (Enter to open, Backspace to go up)" };
+
+    (List::new(
+        browse.entries
+            .visible_indices()
+            .map(|idx| {
+                let entry = &browse.entries.items[idx];
+                let size = browse.entries_size[idx];
+                let mut spans = vec![Span::raw(format!(
+                    "{:>10.2}MB {} {}{}",
+                    size as f32 / 1000000.0,
+                    size_bar(size, total_size, 10),
+                    entry.file_name().to_string_lossy(),
+                    if entry.file_type().is_dir() { "/" } else { "" }
+                ))];
+                spans.extend(non_utf8_marker(is_lossy(entry.file_name()), &theme));
+                let item = ListItem::new(Line::from(spans));
+                if browse.is_marked(idx) {
+                    item.style(theme.selected)
                 } else {
                     item
                 }
@@ -125,21 +593,104 @@ fn content<'a>(state: &mut AppState) -> (List<'a>, &mut ListState) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Plain)
-                .title(format!("Content from path {}", state.path.canonicalize().unwrap_or("Unknown".into()).display()))
+                .title(titled_path("Browse ", &browse.current_dir, title_end, list_width, home_dir.as_deref()))
         )
-        // .highlight_style(Style::default().fg(Color::LightCyan))
+        .highlight_style(theme.highlight)
         .highlight_symbol(">> "),
-        &mut state.entries.state)
+        &mut browse.entries.state)
+}
+
+fn format_time(time: Option<std::time::SystemTime>) -> String {
+    time.map(|time| chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| String::from("Unknown"))
+}
+
+/// Statistics of the highlighted entry, a placeholder while they are `computing`
+fn draw_preview<'a>(preview: Option<&Preview>, computing: bool, theme: &Theme) -> Paragraph<'a> {
+    let title_style = theme.title;
+
+    let lines = match preview {
+        None if computing => vec![Line::from("Computing...")],
+        None => vec![Line::from("Nothing to preview")],
+        Some(preview) => {
+            let mut lines = vec![
+                Line::from(format!("{} files, {:.2}MB", preview.file_count, preview.size as f32 / 1000000.0)),
+                Line::from(format!("Modified: {}", format_time(preview.modified))),
+                Line::from(format!("Created: {}", format_time(preview.created))),
+                Line::from(""),
+                Line::from(Span::styled("Content", title_style)),
+            ];
+            lines.extend(preview.children.iter().map(|child| Line::from(format!(
+                "{}{} {:.2}MB",
+                child.name,
+                if child.is_dir { "/" } else { "" },
+                child.size as f32 / 1000000.0
+            ))));
+
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Largest files", title_style)));
+            lines.extend(preview.largest_files.iter().map(|(path, size)| Line::from(format!(
+                "{} {:.2}MB",
+                path.display(),
+                *size as f32 / 1000000.0
+            ))));
+
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("File types", title_style)));
+            lines.extend(preview.extensions.iter().map(|stats| Line::from(format!(
+                "{}: {} files, {:.2}MB",
+                stats.extension,
+                stats.file_count,
+                stats.size as f32 / 1000000.0
+            ))));
+            lines
+        },
+    };
+
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain)
+                .title("Preview"),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+/// Tail of the log, the most recent record at the bottom
+fn draw_logs<'a>(theme: &Theme, count: u16) -> Paragraph<'a> {
+    let lines = logger::recent_records(count as usize)
+        .into_iter()
+        .map(|record| {
+            let style = match record.level {
+                Level::Error => theme.toast_error,
+                Level::Warn => theme.toast_warn,
+                Level::Info | Level::Debug | Level::Trace => Style::default(),
+            };
+            Line::from(Span::styled(
+                format!("{} {:<5} {}", record.time.format("%H:%M:%S"), record.level, record.message),
+                style,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain)
+                .title("Logs"),
+        )
 }
 
-fn draw_help(actions: &Actions) -> Table {
-    let key_style = Style::default().fg(Color::LightCyan);
-    let help_style = Style::default().fg(Color::Gray);
+fn draw_help<'a>(actions: &Actions, context: &str, theme: &Theme) -> Table<'a> {
+    let key_style = theme.help_key;
+    let help_style = theme.help_text;
 
     let mut rows = vec![];
     for action in actions.slice().iter() {
         let mut first = true;
-        for key in action.keys() {
+        for key in actions.keys(action) {
             let help = if first {
                 first = false;
                 action.to_string()
@@ -147,7 +698,7 @@ fn draw_help(actions: &Actions) -> Table {
                 String::from("")
             };
             let row = Row::new(vec![
-                Cell::from(Span::styled(KeyEventWrapper(&key).to_string(), key_style)),
+                Cell::from(Span::styled(KeySequenceWrapper(key).to_string(), key_style)),
                 Cell::from(Span::styled(help, help_style)),
             ]);
             rows.push(row);
@@ -159,7 +710,7 @@ fn draw_help(actions: &Actions) -> Table {
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Plain)
-                .title("Help"),
+                .title(format!("Help ({})", context)),
         )
         .widths(&[Constraint::Length(11), Constraint::Min(20)])
         .column_spacing(1)
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use crate::utils::walker::DeletionStrategy;
+
+/// Number of deletion batches remembered for undo
+pub const MAX_DELETION_BATCHES: usize = 20;
+
+/// Entries removed by a single deletion
+#[derive(Debug, Clone)]
+pub struct DeletionBatch {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub strategy: DeletionStrategy,
+    /// Canonical paths of the deleted entries, as recorded by the trash and the quarantine
+    pub paths: Vec<PathBuf>,
+    pub size: u64,
+    /// Whether the entries were moved back from the trash or the quarantine
+    pub restored: bool,
+}
+
+impl DeletionBatch {
+    /// Only trashed or quarantined entries can be restored, once
+    pub fn is_restorable(&self) -> bool {
+        self.strategy != DeletionStrategy::Permanent && !self.restored
+    }
+}
+
+/// Recent deletion batches, the most recent last
+#[derive(Debug, Clone, Default)]
+pub struct DeletionHistory(Vec<DeletionBatch>);
+
+impl DeletionHistory {
+    pub fn push(&mut self, batch: DeletionBatch) {
+        self.0.push(batch);
+        let overflow = self.0.len().saturating_sub(MAX_DELETION_BATCHES);
+        self.0.drain(..overflow);
+    }
+
+    pub fn batches(&self) -> &[DeletionBatch] {
+        &self.0
+    }
+
+    pub fn batch_mut(&mut self, idx: usize) -> Option<&mut DeletionBatch> {
+        self.0.get_mut(idx)
+    }
+
+    /// Index of the most recent batch that can be restored
+    pub fn last_restorable(&self) -> Option<usize> {
+        self.0.iter().rposition(DeletionBatch::is_restorable)
+    }
+}
@@ -0,0 +1,581 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{Arguments, actions::DEFAULT_LEADER, searches::SavedSearches, theme::{Theme, ThemeDefinition}};
+use crate::utils::os_path::expand_path;
+use crate::utils::walker::DeletionStrategy;
+
+/// Name of the project-local configuration file, looked up from the current directory to the filesystem root
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".wiper.toml";
+/// Prefix of the environment variables overriding the configuration, e.g. `WIPER_REGEX_FILTER`
+pub const ENV_PREFIX: &str = "WIPER_";
+/// Names of the log file, quarantine and audit log in the state directory, unless set
+const LOG_FILE_NAME: &str = "wiper.log";
+const QUARANTINE_DIR_NAME: &str = "quarantine";
+const AUDIT_LOG_NAME: &str = "audit.jsonl";
+
+/// Where a configuration value comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    System(PathBuf),
+    User(PathBuf),
+    Project(PathBuf),
+    Env(String),
+    /// Search saved from the interface, loaded with `--search`
+    Search(String),
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::System(path) => write!(f, "system config {}", path.display()),
+            ConfigSource::User(path) => write!(f, "user config {}", path.display()),
+            ConfigSource::Project(path) => write!(f, "project config {}", path.display()),
+            ConfigSource::Env(variable) => write!(f, "environment variable {}", variable),
+            ConfigSource::Search(name) => write!(f, "saved search {}", name),
+            ConfigSource::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// A configuration value and the layer it comes from
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: ConfigSource,
+}
+
+impl<T> Setting<T> {
+    fn default(value: T) -> Self {
+        Self { value, source: ConfigSource::Default }
+    }
+
+    fn set(&mut self, value: T, source: &ConfigSource) {
+        self.value = value;
+        self.source = source.clone();
+    }
+}
+
+/// Root paths given as a single path or as a list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RootPaths {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+impl From<RootPaths> for Vec<PathBuf> {
+    fn from(root_paths: RootPaths) -> Self {
+        match root_paths {
+            RootPaths::One(root_path) => vec![root_path],
+            RootPaths::Many(root_paths) => root_paths,
+        }
+    }
+}
+
+/// Content of a single configuration file, every value is optional
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub root_path: Option<RootPaths>,
+    pub regex_filter: Option<String>,
+    /// Name of a preset used as regex filter
+    pub preset: Option<String>,
+    pub prune: Option<bool>,
+    pub deletion_strategy: Option<DeletionStrategy>,
+    pub theme: Option<String>,
+    pub log_file: Option<PathBuf>,
+    /// Directory of the histories of the inputs
+    pub state_dir: Option<PathBuf>,
+    /// Directory the quarantined entries are moved to
+    pub quarantine_dir: Option<PathBuf>,
+    /// JSON-lines file the deletions are appended to
+    pub audit_log: Option<PathBuf>,
+    /// Whether entries containing a `.git` directory can be deleted
+    pub allow_git_deletion: Option<bool>,
+    /// Paths which are never deleted, nor anything inside or containing them
+    pub denylist: Option<Vec<PathBuf>>,
+    /// Key standing for `<leader>` in the key sequences
+    pub leader: Option<String>,
+    /// Named regex filters, e.g. `rust = "^target$"`
+    pub presets: HashMap<String, String>,
+    /// Action names mapped to the keys triggering them, e.g. `Quit = ["ctrl+c", "q"]` or `First = ["g g"]`
+    pub keys: HashMap<String, Vec<String>>,
+    /// Custom themes by name
+    pub themes: HashMap<String, ThemeDefinition>,
+}
+
+impl ConfigLayer {
+    /// Load a configuration file, `None` if it does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Unable to read the configuration file {}", path.display()))?;
+        toml::from_str(&content)
+            .map(Some)
+            .wrap_err_with(|| format!("Invalid configuration file {}", path.display()))
+    }
+
+    /// One layer per `WIPER_*` variable read with `env`, so that each value keeps its own source
+    fn from_env(env: impl Fn(&str) -> Option<String>) -> Result<Vec<(Self, ConfigSource)>> {
+        // the preset comes before the filter so that an explicit filter wins
+        const VARIABLES: [&str; 12] = [
+            "ROOT_PATH", "PRESET", "REGEX_FILTER", "PRUNE", "DELETION_STRATEGY", "THEME", "LOG_FILE", "STATE_DIR",
+            "QUARANTINE_DIR", "AUDIT_LOG", "ALLOW_GIT_DELETION", "DENYLIST",
+        ];
+
+        let mut layers = vec![];
+        for name in VARIABLES {
+            let variable = format!("{}{}", ENV_PREFIX, name);
+            let Some(value) = env(&variable) else { continue; };
+
+            let mut layer = Self::default();
+            match name {
+                "ROOT_PATH" => layer.root_path = Some(RootPaths::Many(std::env::split_paths(&value).collect())),
+                "PRESET" => layer.preset = Some(value),
+                "REGEX_FILTER" => layer.regex_filter = Some(value),
+                "PRUNE" => layer.prune = Some(value.parse().map_err(|_| eyre!("{} must be true or false, got {}", variable, value))?),
+                "DELETION_STRATEGY" => layer.deletion_strategy = Some(value.parse().map_err(|_| eyre!("Invalid deletion strategy {} in {}", value, variable))?),
+                "THEME" => layer.theme = Some(value),
+                "LOG_FILE" => layer.log_file = Some(PathBuf::from(value)),
+                "STATE_DIR" => layer.state_dir = Some(PathBuf::from(value)),
+                "QUARANTINE_DIR" => layer.quarantine_dir = Some(PathBuf::from(value)),
+                "AUDIT_LOG" => layer.audit_log = Some(PathBuf::from(value)),
+                "ALLOW_GIT_DELETION" => layer.allow_git_deletion = Some(value.parse().map_err(|_| eyre!("{} must be true or false, got {}", variable, value))?),
+                "DENYLIST" => layer.denylist = Some(std::env::split_paths(&value).collect()),
+                _ => unreachable!(),
+            }
+            layers.push((layer, ConfigSource::Env(variable)));
+        }
+
+        Ok(layers)
+    }
+
+    fn from_args(args: &Arguments) -> Self {
+        Self {
+            root_path: (!args.root_path.is_empty()).then(|| RootPaths::Many(args.root_path.clone())),
+            regex_filter: args.regex_filter.clone(),
+            preset: args.preset.clone(),
+            prune: args.prune,
+            deletion_strategy: args.deletion_strategy,
+            theme: args.theme.clone(),
+            log_file: args.log_file.clone(),
+            allow_git_deletion: args.allow_git_deletion,
+            ..Default::default()
+        }
+    }
+}
+
+/// Expand `~` and the environment variables of a path setting, as in the path input
+fn expand(path: PathBuf) -> PathBuf {
+    match path.to_str() {
+        Some(path) => expand_path(path, dirs::home_dir().as_deref()),
+        None => path,
+    }
+}
+
+/// Effective configuration, merged from the system, user, project, environment and command line layers
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub root_path: Setting<Vec<PathBuf>>,
+    pub regex_filter: Setting<String>,
+    pub prune: Setting<bool>,
+    pub deletion_strategy: Setting<DeletionStrategy>,
+    pub theme: Setting<String>,
+    pub log_file: Setting<PathBuf>,
+    pub state_dir: Setting<PathBuf>,
+    pub quarantine_dir: Setting<PathBuf>,
+    pub audit_log: Setting<PathBuf>,
+    pub allow_git_deletion: Setting<bool>,
+    pub denylist: Setting<Vec<PathBuf>>,
+    pub leader: Setting<String>,
+    pub presets: BTreeMap<String, Setting<String>>,
+    pub keys: BTreeMap<String, Setting<Vec<String>>>,
+    pub themes: BTreeMap<String, Setting<ThemeDefinition>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            root_path: Setting::default(vec![PathBuf::from(".")]),
+            regex_filter: Setting::default(String::from("^node_modules$")),
+            prune: Setting::default(true),
+            deletion_strategy: Setting::default(DeletionStrategy::default()),
+            theme: Setting::default(String::from("dark")),
+            log_file: Setting::default(Self::default_state_dir().join(LOG_FILE_NAME)),
+            state_dir: Setting::default(Self::default_state_dir()),
+            quarantine_dir: Setting::default(Self::default_state_dir().join(QUARANTINE_DIR_NAME)),
+            audit_log: Setting::default(Self::default_state_dir().join(AUDIT_LOG_NAME)),
+            allow_git_deletion: Setting::default(false),
+            denylist: Setting::default(vec![]),
+            leader: Setting::default(String::from(DEFAULT_LEADER)),
+            presets: BTreeMap::new(),
+            keys: BTreeMap::new(),
+            themes: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// `/etc/wiper/config.toml`
+    pub fn system_path() -> Option<PathBuf> {
+        cfg!(unix).then(|| PathBuf::from("/etc/wiper/config.toml"))
+    }
+
+    /// `~/.config/wiper/config.toml` on Linux, the platform configuration directory otherwise
+    pub fn user_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join("wiper").join("config.toml"))
+    }
+
+    /// `~/.local/state/wiper` on Linux, the platform cache directory otherwise
+    pub fn default_state_dir() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("wiper"))
+            .unwrap_or_default()
+    }
+
+    /// First `.wiper.toml` found in the current directory or its ancestors
+    pub fn project_path() -> Option<PathBuf> {
+        let current_dir = std::env::current_dir().ok()?;
+        current_dir
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Merge every configuration layer, then the saved search given with `--search`,
+    /// the command line having the last word
+    pub fn load(args: &Arguments) -> Result<Self> {
+        let file_layers = [
+            Self::system_path().map(|path| (path.clone(), ConfigSource::System(path))),
+            args.config.clone().or_else(Self::user_path).map(|path| (path.clone(), ConfigSource::User(path))),
+            Self::project_path().map(|path| (path.clone(), ConfigSource::Project(path))),
+        ];
+
+        Self::load_layers(args, file_layers.into_iter().flatten(), |name| std::env::var(name).ok())
+    }
+
+    /// `load` from the given configuration files, by increasing priority, and environment variables
+    fn load_layers(
+        args: &Arguments,
+        file_layers: impl IntoIterator<Item = (PathBuf, ConfigSource)>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut config = Self::default();
+
+        // https://no-color.org, an explicit theme still wins
+        if env("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            config.theme.set(String::from("monochrome"), &ConfigSource::Env(String::from("NO_COLOR")));
+        }
+
+        for (path, source) in file_layers {
+            if let Some(layer) = ConfigLayer::load(&path)? {
+                config.apply(layer, &source)?;
+            }
+        }
+
+        for (layer, source) in ConfigLayer::from_env(env)? {
+            config.apply(layer, &source)?;
+        }
+
+        if let Some(name) = &args.search {
+            let saved_searches = SavedSearches::load(&config.state_dir.value)?;
+            let search = saved_searches
+                .get(name)
+                .ok_or_else(|| eyre!("Unknown saved search {} (in {})", name, SavedSearches::path(&config.state_dir.value).display()))?;
+            config.apply(search.to_layer(), &ConfigSource::Search(name.clone()))?;
+        }
+
+        config.apply(ConfigLayer::from_args(args), &ConfigSource::Cli)?;
+
+        Ok(config)
+    }
+
+    /// Override the values defined by `layer`, expanding `~` and `$VAR` in the paths.
+    /// The log file, quarantine and audit log left unset follow the state directory.
+    /// The denylist is extended rather than overridden, and a project cannot allow deleting git directories
+    pub fn apply(&mut self, layer: ConfigLayer, source: &ConfigSource) -> Result<()> {
+        for (name, regex_filter) in layer.presets {
+            self.presets.insert(name, Setting { value: regex_filter, source: source.clone() });
+        }
+        for (action, keys) in layer.keys {
+            self.keys.insert(action, Setting { value: keys, source: source.clone() });
+        }
+        for (name, theme) in layer.themes {
+            self.themes.insert(name, Setting { value: theme, source: source.clone() });
+        }
+
+        if let Some(root_path) = layer.root_path {
+            self.root_path.set(Vec::from(root_path).into_iter().map(expand).collect(), source);
+        }
+        // an explicit filter wins over a preset of the same layer
+        if let Some(preset) = layer.preset {
+            let regex_filter = self.presets
+                .get(&preset)
+                .ok_or_else(|| eyre!("Unknown preset {} (from {})", preset, source))?
+                .value
+                .clone();
+            self.regex_filter.set(regex_filter, source);
+        }
+        if let Some(regex_filter) = layer.regex_filter {
+            self.regex_filter.set(regex_filter, source);
+        }
+        if let Some(prune) = layer.prune {
+            self.prune.set(prune, source);
+        }
+        if let Some(deletion_strategy) = layer.deletion_strategy {
+            self.deletion_strategy.set(deletion_strategy, source);
+        }
+        if let Some(theme) = layer.theme {
+            self.theme.set(theme, source);
+        }
+        if let Some(state_dir) = layer.state_dir {
+            self.state_dir.set(expand(state_dir), source);
+            for (setting, name) in [(&mut self.log_file, LOG_FILE_NAME), (&mut self.quarantine_dir, QUARANTINE_DIR_NAME), (&mut self.audit_log, AUDIT_LOG_NAME)] {
+                if setting.source == ConfigSource::Default {
+                    setting.value = self.state_dir.value.join(name);
+                }
+            }
+        }
+        if let Some(log_file) = layer.log_file {
+            self.log_file.set(expand(log_file), source);
+        }
+        if let Some(quarantine_dir) = layer.quarantine_dir {
+            self.quarantine_dir.set(expand(quarantine_dir), source);
+        }
+        if let Some(audit_log) = layer.audit_log {
+            self.audit_log.set(expand(audit_log), source);
+        }
+        if let Some(allow_git_deletion) = layer.allow_git_deletion {
+            if allow_git_deletion && matches!(source, ConfigSource::Project(_)) {
+                return Err(eyre!(
+                    "allow_git_deletion cannot be enabled by the {}, set it in the user config or with --allow-git-deletion",
+                    source
+                ));
+            }
+            self.allow_git_deletion.set(allow_git_deletion, source);
+        }
+        // a layer cannot lift the protections of the previous ones
+        if let Some(denylist) = layer.denylist {
+            let mut merged = std::mem::take(&mut self.denylist.value);
+            merged.extend(denylist.into_iter().map(expand));
+            self.denylist.set(merged, source);
+        }
+        if let Some(leader) = layer.leader {
+            self.leader.set(leader, source);
+        }
+
+        Ok(())
+    }
+
+    /// Key bindings overrides, without their source
+    pub fn key_bindings(&self) -> HashMap<String, Vec<String>> {
+        self.keys
+            .iter()
+            .map(|(action, keys)| (action.clone(), keys.value.clone()))
+            .collect()
+    }
+
+    /// Styles of the selected theme
+    pub fn theme(&self) -> Result<Theme> {
+        let definitions = self.themes
+            .iter()
+            .map(|(name, theme)| (name.clone(), theme.value.clone()))
+            .collect();
+        Theme::resolve(&self.theme.value, &definitions)
+            .wrap_err_with(|| format!("Invalid theme (from {})", self.theme.source))
+    }
+
+    /// The effective configuration as TOML, each value commented with its source
+    pub fn show(&self) -> String {
+        fn line<T: Serialize>(name: &str, setting: &Setting<T>) -> String {
+            let value = toml::Value::try_from(&setting.value)
+                .map(|value| value.to_string())
+                .unwrap_or_else(|e| format!("<{}>", e));
+            format!("{} = {} # {}\n", name, value, setting.source)
+        }
+
+        let mut output = String::new();
+        output.push_str(&line("root_path", &self.root_path));
+        output.push_str(&line("regex_filter", &self.regex_filter));
+        output.push_str(&line("prune", &self.prune));
+        output.push_str(&line("deletion_strategy", &self.deletion_strategy));
+        output.push_str(&line("theme", &self.theme));
+        output.push_str(&line("log_file", &self.log_file));
+        output.push_str(&line("state_dir", &self.state_dir));
+        output.push_str(&line("quarantine_dir", &self.quarantine_dir));
+        output.push_str(&line("audit_log", &self.audit_log));
+        output.push_str(&line("allow_git_deletion", &self.allow_git_deletion));
+        output.push_str(&line("denylist", &self.denylist));
+        output.push_str(&line("leader", &self.leader));
+
+        output.push_str("\n[presets]\n");
+        for (name, regex_filter) in &self.presets {
+            output.push_str(&line(name, regex_filter));
+        }
+
+        output.push_str("\n[keys]\n");
+        for (action, keys) in &self.keys {
+            output.push_str(&line(action, keys));
+        }
+
+        output.push_str("\n[themes]\n");
+        for (name, theme) in &self.themes {
+            output.push_str(&line(name, theme));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn layer(toml: &str) -> ConfigLayer {
+        toml::from_str(toml).unwrap()
+    }
+
+    /// Load the given user and project files and environment variables, with `args` on the command line
+    fn load(user: &str, project: &str, env: &[(&str, &str)], args: &[&str]) -> Result<Config> {
+        let dir = tempfile::tempdir().unwrap();
+        let (user_path, project_path) = (dir.path().join("config.toml"), dir.path().join(PROJECT_CONFIG_FILE_NAME));
+        std::fs::write(&user_path, user).unwrap();
+        std::fs::write(&project_path, project).unwrap();
+        let file_layers = [(user_path.clone(), ConfigSource::User(user_path)), (project_path.clone(), ConfigSource::Project(project_path))];
+        let env = env.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<HashMap<_, _>>();
+
+        let args = Arguments::parse_from([&["wiper"], args].concat());
+        Config::load_layers(&args, file_layers, |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn layers_apply_by_increasing_priority() {
+        let config = load(
+            r#"
+                regex_filter = "user"
+                prune = false
+                theme = "light"
+                deletion_strategy = "permanent"
+            "#,
+            r#"
+                regex_filter = "project"
+                prune = true
+                theme = "nord"
+            "#,
+            &[("WIPER_REGEX_FILTER", "env"), ("WIPER_PRUNE", "false")],
+            &["--regex-filter", "cli"],
+        ).unwrap();
+
+        assert_eq!(config.deletion_strategy.value, DeletionStrategy::Permanent);
+        assert!(matches!(config.deletion_strategy.source, ConfigSource::User(_)));
+        assert_eq!(config.theme.value, "nord");
+        assert!(matches!(config.theme.source, ConfigSource::Project(_)));
+        assert!(!config.prune.value);
+        assert_eq!(config.prune.source, ConfigSource::Env(String::from("WIPER_PRUNE")));
+        assert_eq!(config.regex_filter.value, "cli");
+        assert_eq!(config.regex_filter.source, ConfigSource::Cli);
+    }
+
+    #[test]
+    fn explicit_theme_wins_over_no_color() {
+        let config = load("", "", &[("NO_COLOR", "1")], &[]).unwrap();
+        assert_eq!(config.theme.value, "monochrome");
+
+        let config = load(r#"theme = "light""#, "", &[("NO_COLOR", "1")], &[]).unwrap();
+        assert_eq!(config.theme.value, "light");
+    }
+
+    #[test]
+    fn filter_wins_over_a_preset_of_the_same_layer() {
+        let user = r#"
+            preset = "rust"
+            regex_filter = "^dist$"
+            [presets]
+            rust = "^target$"
+            node = "^node_modules$"
+        "#;
+
+        let config = load(user, "", &[], &[]).unwrap();
+        assert_eq!(config.regex_filter.value, "^dist$");
+
+        // a preset of a later layer still wins
+        let config = load(user, r#"preset = "node""#, &[], &[]).unwrap();
+        assert_eq!(config.regex_filter.value, "^node_modules$");
+        let config = load(user, "", &[("WIPER_PRESET", "rust"), ("WIPER_REGEX_FILTER", "^build$")], &["--preset", "node"]).unwrap();
+        assert_eq!(config.regex_filter.value, "^node_modules$");
+        assert_eq!(config.regex_filter.source, ConfigSource::Cli);
+    }
+
+    #[test]
+    fn unknown_preset_is_an_error() {
+        let error = load("[presets]\nrust = \"^target$\"", "", &[("WIPER_PRESET", "node")], &[]).unwrap_err();
+
+        assert_eq!(error.to_string(), "Unknown preset node (from environment variable WIPER_PRESET)");
+    }
+
+    #[test]
+    fn unset_paths_follow_the_state_dir() {
+        let mut config = Config::default();
+        config.apply(layer(r#"audit_log = "/var/log/wiper.jsonl""#), &ConfigSource::Cli).unwrap();
+        config.apply(layer(r#"state_dir = "/srv/wiper""#), &ConfigSource::Cli).unwrap();
+
+        assert_eq!(config.log_file.value, PathBuf::from("/srv/wiper/wiper.log"));
+        assert_eq!(config.quarantine_dir.value, PathBuf::from("/srv/wiper/quarantine"));
+        assert_eq!(config.audit_log.value, PathBuf::from("/var/log/wiper.jsonl"));
+    }
+
+    #[test]
+    fn denylist_is_merged_across_layers() {
+        let mut config = Config::default();
+        config.apply(layer(r#"denylist = ["/srv"]"#), &ConfigSource::System(PathBuf::from("/etc/wiper/config.toml"))).unwrap();
+        config.apply(layer(r#"denylist = []"#), &ConfigSource::Project(PathBuf::from("/work/.wiper.toml"))).unwrap();
+        config.apply(layer(r#"denylist = ["/opt"]"#), &ConfigSource::Env(String::from("WIPER_DENYLIST"))).unwrap();
+
+        assert_eq!(config.denylist.value, vec![PathBuf::from("/srv"), PathBuf::from("/opt")]);
+    }
+
+    #[test]
+    fn project_cannot_allow_git_deletion() {
+        let project = ConfigSource::Project(PathBuf::from("/work/.wiper.toml"));
+        let mut config = Config::default();
+
+        assert!(config.apply(layer("allow_git_deletion = true"), &project).is_err());
+        assert!(!config.allow_git_deletion.value);
+
+        config.apply(layer("allow_git_deletion = false"), &project).unwrap();
+        config.apply(layer("allow_git_deletion = true"), &ConfigSource::User(PathBuf::from("/home/config.toml"))).unwrap();
+        assert!(config.allow_git_deletion.value);
+    }
+
+    #[test]
+    fn paths_are_expanded() {
+        let home_dir = dirs::home_dir().unwrap();
+        let mut config = Config::default();
+        config.apply(layer(r#"
+            root_path = "~/work"
+            state_dir = "~/state"
+            quarantine_dir = "~/q"
+            denylist = ["~/Documents"]
+        "#), &ConfigSource::Cli).unwrap();
+
+        assert_eq!(config.root_path.value, vec![home_dir.join("work")]);
+        assert_eq!(config.state_dir.value, home_dir.join("state"));
+        assert_eq!(config.log_file.value, home_dir.join("state").join("wiper.log"));
+        assert_eq!(config.quarantine_dir.value, home_dir.join("q"));
+        assert_eq!(config.denylist.value, vec![home_dir.join("Documents")]);
+    }
+}
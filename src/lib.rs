@@ -8,7 +8,8 @@ use eyre::{Result, Context};
 use ratatui::{Terminal, backend::CrosstermBackend};
 
 use crossterm::{
-    event::{self, KeyEvent},
+    cursor,
+    event::{self, Event},
     execute,
     terminal,
 };
@@ -16,46 +17,102 @@ use crossterm::{
 use app::{App, AppReturn};
 
 use crate::app::ui;
+use crate::utils::signals::{Signal, Signals, signal_name, suspend_process};
 
 pub mod app;
+pub mod config;
+pub mod logger;
 pub mod utils;
 
-pub fn start_terminal_app(app: &mut App) -> Result<()> {
+/// Report errors and panics with color-eyre, a panic first restoring the terminal so that the report is readable
+pub fn install_hooks() -> Result<()> {
+    let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default()
+        .display_env_section(false)
+        .into_hooks();
+    eyre_hook.install()?;
+
+    std::panic::set_hook(Box::new(move |panic_info| {
+        // the terminal may not be in raw mode yet, nothing to do with an error here
+        let _ = leave_terminal_mode();
+        log::error!("{}", panic_info);
+        log::logger().flush();
+        eprintln!("{}", panic_hook.panic_report(panic_info));
+    }));
+
+    Ok(())
+}
+
+/// Run the interface until it is quit, returning the termination signal received if any.
+/// The terminal is restored and the log flushed on return
+pub fn start_terminal_app(app: &mut App) -> Result<Option<i32>> {
+    let signals = Signals::register()?;
     let mut terminal = setup_terminal()?;
 
-    let loop_error_report = run_loop(app, &mut terminal);
+    let loop_result = run_loop(app, &mut terminal, &signals);
 
     restore_terminal(&mut terminal)?;
+    log::logger().flush();
 
-    if let Err(e) = loop_error_report {
-        println!("Error in the main loop:\n{}", e);
-    }
-
-    Ok(())
+    loop_result.wrap_err("Error in the main loop")
 }
 
-fn run_loop(app: &mut App, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+fn run_loop(app: &mut App, terminal: &mut Terminal<CrosstermBackend<Stdout>>, signals: &Signals) -> Result<Option<i32>> {
     loop {
-        // Check if the terminal is big enough
-        ui::check_size(&terminal.get_frame().size()).context("Unable to continue, the terminal is too small.")?;
-
         terminal.draw(|frame| ui::draw(frame, app))?;
         
-        for key_event in get_key_input_events(Duration::from_millis(200)) {
-            // Check if we should exit
-            if app.key_event(key_event) == AppReturn::Exit {
-                return Ok(());
+        for input_event in get_input_events(Duration::from_millis(200))? {
+            match input_event {
+                Event::Key(key_event) => {
+                    match app.key_event(key_event) {
+                        AppReturn::Exit => return Ok(None),
+                        AppReturn::Suspend => suspend(terminal)?,
+                        AppReturn::Continue => {},
+                    }
+                },
+                Event::Mouse(mouse_event) => app.mouse_event(mouse_event),
+                _ => {},
             }
         }
+
+        match signals.take() {
+            Some(Signal::Terminate(signal)) => {
+                log::info!("Received {}, exiting", signal_name(signal));
+                return Ok(Some(signal));
+            },
+            Some(Signal::Suspend) => suspend(terminal)?,
+            None => {},
+        }
+
+        app.tick();
     }
 }
 
+/// Give the terminal back to the shell until the process is resumed
+fn suspend(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    restore_terminal(terminal)?;
+    suspend_process()?;
 
-fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
-    let mut stdout = io::stdout();
+    enter_terminal_mode()?;
+    terminal.clear()?;
+    terminal.hide_cursor()?;
+    Ok(())
+}
+
+fn enter_terminal_mode() -> Result<()> {
     terminal::enable_raw_mode()?;
-    execute!(stdout, terminal::EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    execute!(io::stdout(), terminal::EnterAlternateScreen, event::EnableMouseCapture)?;
+    Ok(())
+}
+
+fn leave_terminal_mode() -> Result<()> {
+    terminal::disable_raw_mode()?;
+    execute!(io::stdout(), terminal::LeaveAlternateScreen, event::DisableMouseCapture, cursor::Show)?;
+    Ok(())
+}
+
+fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    enter_terminal_mode()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
     terminal.hide_cursor()?;
@@ -65,20 +122,21 @@ fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
 fn restore_terminal(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
 ) -> Result<()> {
-    terminal::disable_raw_mode()?;
-    execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
+    leave_terminal_mode()?;
     terminal.show_cursor()?;
     Ok(())
 }
 
-pub fn get_key_input_events(tick_rate: Duration) -> Vec<KeyEvent> {
-    let mut input_events: Vec<KeyEvent> = Vec::new();
+/// Collect the key and mouse events received during `tick_rate`
+pub fn get_input_events(tick_rate: Duration) -> Result<Vec<Event>> {
+    let mut input_events: Vec<Event> = Vec::new();
 
-    while event::poll(tick_rate).context("event poll failed").unwrap()  {
-        if let event::Event::Key(key_event) = event::read().context("event read failed").unwrap() {
-            input_events.push(key_event);
+    while event::poll(tick_rate).context("event poll failed")? {
+        let input_event = event::read().context("event read failed")?;
+        if matches!(input_event, Event::Key(_) | Event::Mouse(_)) {
+            input_events.push(input_event);
         }
     }
 
-    input_events
+    Ok(input_events)
 }
\ No newline at end of file
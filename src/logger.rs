@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use eyre::{Result, WrapErr};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Size above which the log file is rotated
+const MAX_LOG_FILE_SIZE: u64 = 1_000_000;
+/// Number of rotated files kept next to the current one, `wiper.log.1` being the most recent
+const ROTATED_LOG_FILES: usize = 3;
+/// Number of records kept in memory for the log panel
+const RECENT_RECORDS_COUNT: usize = 200;
+
+/// Record kept in memory for the log panel
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+static RECENT_RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+
+/// The most recent records, oldest first
+pub fn recent_records(count: usize) -> Vec<LogRecord> {
+    let records = RECENT_RECORDS.lock().unwrap_or_else(|e| e.into_inner());
+    records.iter().skip(records.len().saturating_sub(count)).cloned().collect()
+}
+
+/// Verbosity given by `RUST_LOG` if set, by the number of `-v` otherwise.
+/// Only the levels of `RUST_LOG` are used, e.g. `debug` or `wiper=trace,walkdir=info` (the most verbose wins)
+pub fn level_filter(verbose: u8) -> LevelFilter {
+    let from_env = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|directives| directives
+            .split(',')
+            .filter_map(|directive| directive.rsplit('=').next()?.trim().parse::<LevelFilter>().ok())
+            .max());
+
+    from_env.unwrap_or(match verbose {
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    })
+}
+
+/// Writes the records to a size-rotated file and keeps the recent ones for the log panel
+struct FileLogger {
+    path: PathBuf,
+    /// `None` when the file could not be opened, the records being only kept in memory
+    file: Option<Mutex<File>>,
+    level: LevelFilter,
+}
+
+impl FileLogger {
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        PathBuf::from(path)
+    }
+
+    /// Shift the rotated files and start a new one, the oldest being dropped
+    fn rotate(&self, file: &mut File) -> std::io::Result<()> {
+        for n in (1..ROTATED_LOG_FILES).rev() {
+            let rotated_path = self.rotated_path(n);
+            if rotated_path.exists() {
+                fs::rename(&rotated_path, self.rotated_path(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        *file = open_log_file(&self.path)?;
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let record = LogRecord {
+            time: chrono::Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+
+        // nowhere to report a failure to log, the record is still shown in the panel
+        if let Some(Ok(mut file)) = self.file.as_ref().map(Mutex::lock) {
+            let _ = writeln!(file, "{} {:<5} [{}] {}", record.time.format("%Y-%m-%d %H:%M:%S%.3f"), record.level, record.target, record.message);
+            if file.metadata().is_ok_and(|metadata| metadata.len() > MAX_LOG_FILE_SIZE) {
+                let _ = self.rotate(&mut file);
+            }
+        }
+
+        let mut records = RECENT_RECORDS.lock().unwrap_or_else(|e| e.into_inner());
+        records.push_back(record);
+        if records.len() > RECENT_RECORDS_COUNT {
+            records.pop_front();
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(Ok(mut file)) = self.file.as_ref().map(Mutex::lock) {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn open_log_file(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn create_log_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .wrap_err_with(|| format!("Unable to create the log directory {}", parent.display()))?;
+    }
+    open_log_file(path).wrap_err_with(|| format!("Unable to open the log file {}", path.display()))
+}
+
+/// Install the logger, writing to `path`.
+/// The logger is installed even when the file cannot be opened, keeping the records in memory
+/// for the log panel, and the error is returned to be reported as a warning
+pub fn init(path: &Path, level: LevelFilter) -> Result<()> {
+    let (file, error) = match create_log_file(path) {
+        Ok(file) => (Some(Mutex::new(file)), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    log::set_boxed_logger(Box::new(FileLogger { path: path.to_path_buf(), file, level }))
+        .wrap_err("Unable to install the logger")?;
+    log::set_max_level(level);
+
+    error.map_or(Ok(()), Err)
+}
@@ -1,16 +1,101 @@
 use clap::Parser;
-use eyre::Result;
+use eyre::{Result, WrapErr};
 
-use wiper::{app::{App, Arguments}, start_terminal_app};
+use wiper::{app::{App, Arguments, Command, ConfigCommand}, config::Config, logger, start_terminal_app};
+use wiper::utils::{audit::{self, AuditResult}, quarantine};
 
 fn main() -> Result<()> {
+    wiper::install_hooks()?;
     let args = Arguments::parse();
+
+    let config = Config::load(&args)?;
+
+    if let Some(Command::Config { command: ConfigCommand::Show }) = args.command {
+        print!("{}", config.show());
+        return Ok(());
+    }
+
+    // not fatal, the records are still shown in the log panel
+    let log_error = logger::init(&config.log_file.value, logger::level_filter(args.verbose)).err();
+    if let (Some(e), Some(_)) = (&log_error, &args.command) {
+        eprintln!("Warning: {:#}, not logging to a file", e);
+    }
     log::debug!("{:?}", args);
 
+    match args.command {
+        Some(Command::Purge { older_than, dry_run }) => {
+            let quarantine_dir = &config.quarantine_dir.value;
+            let purged = quarantine::purge(quarantine_dir, older_than, dry_run)
+                .wrap_err_with(|| format!("Unable to purge the quarantine {}", quarantine_dir.display()))?;
+            for entry in &purged {
+                println!(
+                    "{}  {}  {:.2}MB  {}",
+                    entry.id,
+                    entry.manifest.quarantined_at.format("%Y-%m-%d %H:%M"),
+                    entry.manifest.size as f32 / 1000000.0,
+                    entry.manifest.original_path.display()
+                );
+            }
+            let size: u64 = purged.iter().map(|entry| entry.manifest.size).sum();
+            let verb = if dry_run { "Would purge" } else { "Purged" };
+            println!("{} {} entries, {:.2}MB", verb, purged.len(), size as f32 / 1000000.0);
+            return Ok(());
+        },
+        Some(Command::Restore { id }) => {
+            let quarantine_dir = &config.quarantine_dir.value;
+            let path = quarantine::restore(quarantine_dir, &id)
+                .wrap_err_with(|| format!("Unable to restore {} from the quarantine {}", id, quarantine_dir.display()))?;
+            println!("Restored {} to {}", id, path.display());
+            return Ok(());
+        },
+        Some(Command::History { since, path, user, failed, limit, json }) => {
+            let audit_log = &config.audit_log.value;
+            let filter = audit::AuditFilter { since, path, user, failed_only: failed };
+            let mut records = audit::read(audit_log)
+                .wrap_err_with(|| format!("Unable to read the audit log {}", audit_log.display()))?;
+            records.retain(|record| filter.matches(record));
+            let skipped = limit.map_or(0, |limit| records.len().saturating_sub(limit));
+            for record in &records[skipped..] {
+                if json {
+                    println!("{}", serde_json::to_string(record)?);
+                    continue;
+                }
+                let result = match (record.result, &record.error) {
+                    (AuditResult::Refused, Some(error)) => format!("refused: {}", error),
+                    (_, Some(error)) => format!("failed: {}", error),
+                    (_, None) => String::from("ok"),
+                };
+                let user = match &record.sudo_user {
+                    Some(sudo_user) => format!("{}(sudo:{})", record.user, sudo_user),
+                    None => record.user.clone(),
+                };
+                println!(
+                    "{}  {}@{}  {}  {:.2}MB  {} files  {}  {}",
+                    record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    user,
+                    record.host,
+                    record.strategy,
+                    record.size as f32 / 1000000.0,
+                    record.file_count,
+                    record.path.display(),
+                    result
+                );
+            }
+            return Ok(());
+        },
+        _ => {},
+    }
+
     // We need to share the App between thread
-    let mut app = App::new_from_args(&args);
+    let mut app = App::new_from_config(&config)?;
+    if let Some(e) = log_error {
+        app.state_mut().toasts.warn(format!("{:#}, the log is only kept in memory", e));
+    }
 
-    start_terminal_app(&mut app)?;
+    // exit the way the signal would have, as shells and supervisors expect
+    if let Some(signal) = start_terminal_app(&mut app)? {
+        std::process::exit(128 + signal);
+    }
 
     Ok(())
 }
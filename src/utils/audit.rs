@@ -0,0 +1,181 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::os_path::lossless_path;
+use crate::utils::walker::DeletionStrategy;
+
+/// Outcome of the deletion of an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditResult {
+    Ok,
+    Failed,
+    /// Protected by the guardrails, not attempted
+    Refused,
+}
+
+/// Deletion of an entry, appended as one JSON line to the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Local>,
+    /// Owner of the process, `root` under sudo
+    pub user: String,
+    /// User who ran sudo, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sudo_user: Option<String>,
+    pub host: String,
+    /// Exact even when it is not valid UTF-8
+    #[serde(with = "lossless_path")]
+    pub path: PathBuf,
+    pub size: u64,
+    pub file_count: usize,
+    pub strategy: DeletionStrategy,
+    pub result: AuditResult,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Id to restore the entry with `wiper restore`, for the quarantine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine_id: Option<String>,
+}
+
+impl AuditRecord {
+    /// Record of `path` deleted now by the current user
+    pub fn new(path: PathBuf, file_count: usize, size: u64, strategy: DeletionStrategy) -> Self {
+        Self {
+            timestamp: Local::now(),
+            user: current_user(),
+            sudo_user: std::env::var("SUDO_USER").ok().filter(|user| !user.is_empty()),
+            host: host_name(),
+            path,
+            size,
+            file_count,
+            strategy,
+            result: AuditResult::Ok,
+            error: None,
+            quarantine_id: None,
+        }
+    }
+
+    pub fn failed(mut self, error: &io::Error) -> Self {
+        self.result = AuditResult::Failed;
+        self.error = Some(error.to_string());
+        self
+    }
+
+    pub fn refused(mut self, reason: &str) -> Self {
+        self.result = AuditResult::Refused;
+        self.error = Some(reason.to_string());
+        self
+    }
+}
+
+/// Criteria of the records listed by `wiper history`, all of them when empty
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only the records more recent than this age
+    pub since: Option<chrono::Duration>,
+    /// Only the records whose path contains this text
+    pub path: Option<String>,
+    /// Only the records of this user, directly or through sudo
+    pub user: Option<String>,
+    /// Only the failed and refused deletions
+    pub failed_only: bool,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        if let Some(since) = self.since {
+            if record.timestamp < Local::now() - since {
+                return false;
+            }
+        }
+        if let Some(path) = &self.path {
+            if !record.path.to_string_lossy().contains(path.as_str()) {
+                return false;
+            }
+        }
+        if let Some(user) = &self.user {
+            if record.user != *user && record.sudo_user.as_ref() != Some(user) {
+                return false;
+            }
+        }
+        !self.failed_only || record.result != AuditResult::Ok
+    }
+}
+
+/// Append `record` to the audit log, creating it if needed
+pub fn append(audit_log: &Path, record: &AuditRecord) -> io::Result<()> {
+    if let Some(parent) = audit_log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    OpenOptions::new().create(true).append(true).open(audit_log)?.write_all(line.as_bytes())
+}
+
+/// Records of the audit log, oldest first, none if it does not exist yet.
+/// Lines which cannot be parsed are skipped with a warning
+pub fn read(audit_log: &Path) -> io::Result<Vec<AuditRecord>> {
+    let content = match fs::read_to_string(audit_log) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(idx, line)| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!("Skipping the invalid line {} of the audit log {}: {}", idx + 1, audit_log.display(), e);
+                None
+            },
+        })
+        .collect())
+}
+
+/// Name of the real user of the process, the uid when it has no passwd entry.
+/// Not read from `$USER`, which the caller can set to anything
+#[cfg(unix)]
+fn current_user() -> String {
+    let uid = unsafe { libc::getuid() };
+    let mut passwd = std::mem::MaybeUninit::<libc::passwd>::uninit();
+    let mut buffer = vec![0 as libc::c_char; 1024];
+    let mut result = std::ptr::null_mut();
+    loop {
+        let code = unsafe { libc::getpwuid_r(uid, passwd.as_mut_ptr(), buffer.as_mut_ptr(), buffer.len(), &mut result) };
+        if code != libc::ERANGE || buffer.len() >= 1 << 20 {
+            break;
+        }
+        buffer.resize(buffer.len() * 2, 0);
+    }
+
+    if result.is_null() {
+        return uid.to_string();
+    }
+    // `result` points to `passwd`, whose strings are in `buffer`
+    unsafe { std::ffi::CStr::from_ptr((*result).pw_name) }.to_string_lossy().into_owned()
+}
+
+#[cfg(not(unix))]
+fn current_user() -> String {
+    std::env::var("USERNAME").unwrap_or_else(|_| String::from("unknown"))
+}
+
+fn host_name() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_else(|| String::from("unknown"))
+}
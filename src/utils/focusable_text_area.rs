@@ -1,5 +1,5 @@
-use ratatui::{style::{Style, Modifier, Color}, widgets::{Block, Borders, Widget}};
-use tui_textarea::TextArea;
+use ratatui::{style::{Style, Modifier}, widgets::{Block, Borders, Widget}};
+use tui_textarea::{CursorMove, TextArea};
 
 #[derive(Clone)]
 pub struct FocusableTextArea<'a> {
@@ -7,6 +7,10 @@ pub struct FocusableTextArea<'a> {
     pub focused: bool,
     focused_title: String,
     unfocused_title: String,
+    /// Appended to the focused title, e.g. the validation of the input
+    status: Option<String>,
+    focused_style: Style,
+    unfocused_style: Style,
 }
 
 impl<'a> Default for FocusableTextArea<'a> {
@@ -27,6 +31,9 @@ impl<'a> FocusableTextArea<'a> {
             focused: false,
             focused_title: focused_title.into(),
             unfocused_title: unfocused_title.into(),
+            status: None,
+            focused_style: Style::default(),
+            unfocused_style: Style::default(),
         };
 
         focusable_text_area.set_unfocus_style();
@@ -44,8 +51,11 @@ impl<'a> FocusableTextArea<'a> {
         self.text_area.set_block(
             Block::default()
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::LightGreen))
-            .title(self.focused_title.clone())
+            .style(self.focused_style)
+            .title(match &self.status {
+                Some(status) => format!("{} - {}", self.focused_title, status),
+                None => self.focused_title.clone(),
+            })
         );
     }
 
@@ -54,11 +64,30 @@ impl<'a> FocusableTextArea<'a> {
         self.text_area.set_cursor_style(Style::default());
         self.text_area.set_block(
             Block::default().borders(Borders::ALL)
-            .style(Style::default().fg(Color::DarkGray))
+            .style(self.unfocused_style)
             .title(self.unfocused_title.clone())
         );
     }
 
+    /// Styles of the block when focused and unfocused
+    pub fn set_styles(&mut self, focused_style: Style, unfocused_style: Style) {
+        self.focused_style = focused_style;
+        self.unfocused_style = unfocused_style;
+        self.set_focus(self.focused);
+    }
+
+    pub fn set_status(&mut self, status: Option<String>) {
+        self.status = status;
+        self.set_focus(self.focused);
+    }
+
+    /// Replace the content, the cursor being moved to its end
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text_area = TextArea::new(vec![text.into()]);
+        self.text_area.move_cursor(CursorMove::End);
+        self.set_focus(self.focused);
+    }
+
     pub fn set_focus(&mut self, focused: bool) {
         self.focused = focused;
         if focused {
@@ -0,0 +1,134 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::utils::os_path::tilde_path;
+
+/// Critical paths `delete_entries` refuses to remove
+#[derive(Debug, Clone, Default)]
+pub struct DeletionGuard {
+    /// Scanned roots, which are never deleted themselves
+    pub roots: Vec<PathBuf>,
+    pub home_dir: Option<PathBuf>,
+    /// Where the quarantined entries are kept, which cannot be deleted nor anything inside or containing it
+    pub quarantine_dir: Option<PathBuf>,
+    /// Whether entries containing a `.git` directory can be deleted
+    pub allow_git: bool,
+    /// Paths which cannot be deleted, nor anything inside or containing them
+    pub denylist: Vec<PathBuf>,
+}
+
+impl DeletionGuard {
+    /// Why `path` cannot be deleted, `None` if it can. `git_dir` is the first `.git` directory found in `path`
+    /// when it was measured, and `mount_points` those of the system, read once for every path to check
+    pub fn check(&self, path: &Path, git_dir: Option<&Path>, mount_points: &[PathBuf]) -> Option<String> {
+        let path = canonical(path);
+        let display = |other: &Path| tilde_path(other, self.home_dir.as_deref());
+
+        if path.parent().is_none() {
+            return Some(String::from("it is the filesystem root"));
+        }
+        if self.roots.iter().any(|root| canonical(root) == path) {
+            return Some(String::from("it is a scanned root"));
+        }
+        if let Some(home_dir) = self.home_dir.as_deref().map(canonical) {
+            if home_dir == path {
+                return Some(String::from("it is the home directory"));
+            }
+            if home_dir.starts_with(&path) {
+                return Some(String::from("it contains the home directory"));
+            }
+        }
+        if let Some(quarantine_dir) = self.quarantine_dir.as_deref().map(canonical) {
+            if path.starts_with(&quarantine_dir) {
+                return Some(format!("it is in the quarantine directory {}", display(&quarantine_dir)));
+            }
+            if quarantine_dir.starts_with(&path) {
+                return Some(format!("it contains the quarantine directory {}", display(&quarantine_dir)));
+            }
+        }
+        for denied in self.denylist.iter().map(|denied| canonical(denied)) {
+            if path.starts_with(&denied) {
+                return Some(format!("{} is in the denylist", display(&denied)));
+            }
+            if denied.starts_with(&path) {
+                return Some(format!("it contains {} of the denylist", display(&denied)));
+            }
+        }
+        if is_mount_point(&path) {
+            return Some(String::from("it is a mount point"));
+        }
+        if let Some(mount_point) = mount_points.iter().find(|mount_point| mount_point.starts_with(&path)) {
+            return Some(format!("it contains the mount point {}", display(mount_point)));
+        }
+        if !self.allow_git {
+            if path.file_name() == Some(OsStr::new(".git")) {
+                return Some(String::from("it is a git directory, set allow_git_deletion to delete it"));
+            }
+            if let Some(git_dir) = git_dir {
+                return Some(format!("it contains the git directory {}, set allow_git_deletion to delete it", display(git_dir)));
+            }
+        }
+        None
+    }
+}
+
+/// Canonical form of `path`, as is if it cannot be resolved
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Whether `path` is on another device than its parent
+#[cfg(unix)]
+fn is_mount_point(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(parent) = path.parent() else { return true; };
+    match (std::fs::symlink_metadata(path), std::fs::metadata(parent)) {
+        (Ok(metadata), Ok(parent_metadata)) => metadata.is_dir() && metadata.dev() != parent_metadata.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_mount_point(path: &Path) -> bool {
+    path.parent().is_none()
+}
+
+/// Mount points of the system, including those sharing the device of their parent (bind mounts)
+#[cfg(target_os = "linux")]
+pub fn mount_points() -> Vec<PathBuf> {
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else { return vec![]; };
+    mounts
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|mount_point| PathBuf::from(unescape_mount_point(mount_point)))
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mount_points() -> Vec<PathBuf> {
+    vec![]
+}
+
+/// Spaces, tabs, newlines and backslashes are octal escapes in `/proc/self/mounts`, e.g. `\040`
+#[cfg(target_os = "linux")]
+fn unescape_mount_point(mount_point: &str) -> String {
+    let mut unescaped = String::with_capacity(mount_point.len());
+    let mut rest = mount_point;
+    while let Some(idx) = rest.find('\\') {
+        unescaped.push_str(&rest[..idx]);
+        let code = rest.get(idx + 1..idx + 4).and_then(|code| u8::from_str_radix(code, 8).ok());
+        match code {
+            Some(code) => {
+                unescaped.push(char::from(code));
+                rest = &rest[idx + 4..];
+            },
+            None => {
+                unescaped.push('\\');
+                rest = &rest[idx + 1..];
+            },
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
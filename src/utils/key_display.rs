@@ -15,6 +15,7 @@ impl std::fmt::Display for KeyEventWrapper<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 
         let key_code_string = match self.code {
+            KeyCode::Char(' ') => String::from("space"),
             KeyCode::Char(c) => c.to_string(),
             KeyCode::F(n) => format!("F{}", n),
             _ => format!("{:?}", self.code),
@@ -38,4 +39,161 @@ impl std::fmt::Display for KeyEventWrapper<'_> {
             write!(f, "{}+{}", modifiers_str, key_code_string)
         }
     }
-}
\ No newline at end of file
+}
+
+fn parse_key_code(s: &str) -> Result<KeyCode, String> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    let key_code = match s.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "enter" => KeyCode::Enter,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "esc" => KeyCode::Esc,
+        function_key if function_key.starts_with('f') => function_key[1..]
+            .parse::<u8>()
+            .map(KeyCode::F)
+            .map_err(|_| format!("unknown key {}", s))?,
+        _ => return Err(format!("unknown key {}", s)),
+    };
+    Ok(key_code)
+}
+
+/// Parse a key written the way `KeyEventWrapper` displays it, e.g. `ctrl+c`, `shift+A`, `PageUp` or `F5`
+pub fn parse_key_event(s: &str) -> Result<KeyEvent, String> {
+    // the '+' key itself may follow the modifiers separator
+    let (modifiers_str, key_code_str) = if let Some(modifiers_str) = s.strip_suffix("++") {
+        (modifiers_str, "+")
+    } else {
+        match s.rsplit_once('+') {
+            Some((modifiers_str, key_code_str)) if !key_code_str.is_empty() => (modifiers_str, key_code_str),
+            _ => ("", s),
+        }
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in modifiers_str.split('+').filter(|modifier| !modifier.is_empty()) {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "shift" => KeyModifiers::SHIFT,
+            "alt" => KeyModifiers::ALT,
+            _ => return Err(format!("unknown modifier {} in {}", modifier, s)),
+        };
+    }
+
+    let key_code = parse_key_code(key_code_str)?;
+
+    // uppercase letters are received with the shift modifier
+    if matches!(key_code, KeyCode::Char(c) if c.is_ascii_uppercase()) {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+
+    Ok(KeyEvent::new(key_code, modifiers))
+}
+
+/// Keys pressed one after the other, displayed separated by spaces, e.g. `g g`
+pub struct KeySequenceWrapper<'a>(pub &'a [KeyEvent]);
+
+impl std::fmt::Display for KeySequenceWrapper<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, key) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", KeyEventWrapper(key))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse space separated keys the way `KeySequenceWrapper` displays them, e.g. `g g` or `<leader> d`,
+/// `<leader>` standing for the configured leader key
+pub fn parse_key_sequence(s: &str, leader: &KeyEvent) -> Result<Vec<KeyEvent>, String> {
+    let sequence = s
+        .split_whitespace()
+        .map(|key| match key {
+            "<leader>" => Ok(*leader),
+            _ => parse_key_event(key),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if sequence.is_empty() {
+        return Err(String::from("empty key sequence"));
+    }
+    Ok(sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn displayed_keys_are_parsed_back() {
+        for key in [
+            key(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            key(KeyCode::Char('+'), KeyModifiers::CONTROL),
+            key(KeyCode::Char(' '), KeyModifiers::NONE),
+            key(KeyCode::F(5), KeyModifiers::NONE),
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL | KeyModifiers::ALT),
+            key(KeyCode::PageUp, KeyModifiers::NONE),
+        ] {
+            let displayed = KeyEventWrapper(&key).to_string();
+            assert_eq!(parse_key_event(&displayed), Ok(key), "{}", displayed);
+        }
+    }
+
+    #[test]
+    fn keys_are_parsed() {
+        assert_eq!(parse_key_event("shift+A"), Ok(key(KeyCode::Char('A'), KeyModifiers::SHIFT)));
+        assert_eq!(parse_key_event("A"), Ok(key(KeyCode::Char('A'), KeyModifiers::SHIFT)));
+        assert_eq!(parse_key_event("ctrl++"), Ok(key(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key_event("+"), Ok(key(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(parse_key_event("space"), Ok(key(KeyCode::Char(' '), KeyModifiers::NONE)));
+        assert_eq!(parse_key_event("F5"), Ok(key(KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(parse_key_event("Ctrl+PageDown"), Ok(key(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn invalid_keys_are_errors() {
+        assert_eq!(parse_key_event("hyper+a"), Err(String::from("unknown modifier hyper in hyper+a")));
+        assert_eq!(parse_key_event("ctrl+foo"), Err(String::from("unknown key foo")));
+        assert_eq!(parse_key_event("Fx"), Err(String::from("unknown key Fx")));
+    }
+
+    #[test]
+    fn sequences_are_parsed_with_the_leader() {
+        let leader = key(KeyCode::Char('\\'), KeyModifiers::NONE);
+        let d = key(KeyCode::Char('d'), KeyModifiers::NONE);
+
+        let sequence = parse_key_sequence("<leader> d", &leader).unwrap();
+        assert_eq!(sequence, vec![leader, d]);
+        assert_eq!(parse_key_sequence(&KeySequenceWrapper(&sequence).to_string(), &leader), Ok(sequence));
+        assert_eq!(parse_key_sequence("g  g", &leader), Ok(vec![key(KeyCode::Char('g'), KeyModifiers::NONE); 2]));
+    }
+
+    #[test]
+    fn invalid_sequences_are_errors() {
+        let leader = key(KeyCode::Char('\\'), KeyModifiers::NONE);
+
+        assert_eq!(parse_key_sequence(" ", &leader), Err(String::from("empty key sequence")));
+        assert_eq!(parse_key_sequence("g foo", &leader), Err(String::from("unknown key foo")));
+    }
+}
@@ -1,4 +1,9 @@
 pub mod walker;
 pub mod statefull_list;
 pub mod key_display;
-pub mod focusable_text_area;
\ No newline at end of file
+pub mod os_path;
+pub mod focusable_text_area;
+pub mod signals;
+pub mod quarantine;
+pub mod audit;
+pub mod guard;
\ No newline at end of file
@@ -0,0 +1,361 @@
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::io::ErrorKind;
+use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+
+/// Bytes of a file name or path as matched by the regex filters.
+/// On Unix they are the exact bytes, so that names which are not valid UTF-8 can be matched,
+/// e.g. with `(?-u:\xE9)` for a Latin-1 `é`. Elsewhere invalid sequences are replaced by `�`
+pub fn os_str_bytes(s: &OsStr) -> Cow<'_, [u8]> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Cow::Borrowed(s.as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        match s.to_string_lossy() {
+            Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+            Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+        }
+    }
+}
+
+/// Whether displaying `s` requires replacing invalid sequences by `�`
+pub fn is_lossy(s: &OsStr) -> bool {
+    s.to_str().is_none()
+}
+
+/// Display `path` with the home directory replaced by `~`
+pub fn tilde_path(path: &Path, home_dir: Option<&Path>) -> String {
+    match home_dir.and_then(|home_dir| path.strip_prefix(home_dir).ok()) {
+        Some(relative) if relative.as_os_str().is_empty() => String::from("~"),
+        Some(relative) => format!("~{}{}", MAIN_SEPARATOR, relative.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// Absolute path of `path` with the links of its parent resolved, but not `path` itself:
+/// a link stays the link and not its target, as the trash records it
+pub fn canonicalize_parent(path: &Path) -> std::io::Result<PathBuf> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(file_name)) => {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            Ok(parent.canonicalize()?.join(file_name))
+        },
+        _ => path.canonicalize(),
+    }
+}
+
+/// Expand a leading `~` to the home directory and `$VAR` or `${VAR}` to the value of the environment variable,
+/// unset variables being kept as written
+pub fn expand_path(input: &str, home_dir: Option<&Path>) -> PathBuf {
+    let mut expanded = OsString::new();
+    let mut rest = input;
+    if let Some(home_dir) = home_dir {
+        if let Some(after_tilde) = input.strip_prefix('~').filter(|after| after.is_empty() || after.starts_with(std::path::is_separator)) {
+            expanded.push(home_dir);
+            rest = after_tilde;
+        }
+    }
+
+    while let Some(dollar) = rest.find('$') {
+        expanded.push(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let (name, written_len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                (&after[..end], end)
+            },
+        };
+
+        match std::env::var_os(name).filter(|_| !name.is_empty()) {
+            Some(value) => expanded.push(value),
+            None => expanded.push(&rest[dollar..dollar + 1 + written_len]),
+        }
+        rest = &after[written_len..];
+    }
+    expanded.push(rest);
+
+    PathBuf::from(expanded)
+}
+
+/// Why `path` cannot be scanned, if it is not a readable directory
+pub fn validate_root(path: &Path) -> Result<(), String> {
+    let metadata = std::fs::metadata(path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => String::from("does not exist"),
+        ErrorKind::PermissionDenied => String::from("permission denied"),
+        _ => e.to_string(),
+    })?;
+    if !metadata.is_dir() {
+        return Err(String::from("not a directory"));
+    }
+    std::fs::read_dir(path).map_err(|e| format!("not readable: {}", e))?;
+    Ok(())
+}
+
+/// Why the roots cannot be scanned, naming the faulty root
+pub fn validate_roots(roots: &[PathBuf]) -> Result<(), String> {
+    if roots.is_empty() {
+        return Err(String::from("no root path"));
+    }
+    for root in roots {
+        validate_root(root).map_err(|e| format!("{}: {}", root.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Result of completing the last component of a path being typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathCompletion {
+    /// The input extended with the longest prefix shared by the candidates
+    pub text: String,
+    /// Names of the directories matching the last component, sorted
+    pub candidates: Vec<String>,
+}
+
+/// Complete the last component of `input` with the names of the directories it prefixes.
+/// Hidden directories are only offered when the component starts with a dot
+pub fn complete_directory(input: &str, home_dir: Option<&Path>) -> PathCompletion {
+    let (parent, prefix) = match input.rfind(std::path::is_separator) {
+        Some(idx) => input.split_at(idx + 1),
+        None => ("", input),
+    };
+    let dir = if parent.is_empty() { PathBuf::from(".") } else { expand_path(parent, home_dir) };
+
+    let mut candidates: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    candidates.sort();
+
+    let text = match candidates.as_slice() {
+        [] => input.to_string(),
+        [name] => format!("{}{}{}", parent, name, MAIN_SEPARATOR),
+        [first, others @ ..] => {
+            let common_len = others.iter().fold(first.len(), |len, name| {
+                first[..len]
+                    .char_indices()
+                    .zip(name.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(len.min(name.len()), |((idx, _), _)| idx)
+            });
+            format!("{}{}", parent, &first[..common_len])
+        },
+    };
+
+    PathCompletion { text, candidates }
+}
+
+/// Separator of the roots typed in a single input, as in `PATH`
+pub const ROOTS_SEPARATOR: char = if cfg!(windows) { ';' } else { ':' };
+
+/// Roots of an input separated by `ROOTS_SEPARATOR`, blank ones being ignored
+pub fn split_roots(input: &str) -> impl Iterator<Item = &str> {
+    input.split(ROOTS_SEPARATOR).map(str::trim).filter(|root| !root.is_empty())
+}
+
+/// Drop the roots equal to or inside another root, which would be scanned twice.
+/// Returns the kept roots in their original order and each dropped root with the root covering it
+pub fn dedupe_roots(roots: &[PathBuf]) -> (Vec<PathBuf>, Vec<(PathBuf, PathBuf)>) {
+    let canonical_roots = roots
+        .iter()
+        .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+        .collect::<Vec<_>>();
+
+    let mut kept = vec![];
+    let mut dropped = vec![];
+    for (idx, root) in roots.iter().enumerate() {
+        // of two identical roots the first one is kept
+        let covering = (0..roots.len()).find(|&other| {
+            other != idx
+                && canonical_roots[idx].starts_with(&canonical_roots[other])
+                && (canonical_roots[idx] != canonical_roots[other] || other < idx)
+        });
+        match covering {
+            Some(other) => dropped.push((root.clone(), roots[other].clone())),
+            None => kept.push(root.clone()),
+        }
+    }
+
+    (kept, dropped)
+}
+
+/// Deepest directory containing every path
+pub fn common_ancestor(paths: &[PathBuf]) -> PathBuf {
+    let Some((first, others)) = paths.split_first() else { return PathBuf::new(); };
+    others.iter().fold(first.clone(), |ancestor, path| {
+        ancestor
+            .components()
+            .zip(path.components())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    })
+}
+
+/// Serde representation keeping paths exact: a string when valid UTF-8, otherwise the raw bytes
+/// next to a lossy display, e.g. `{ display = "caf�", bytes = [99, 97, 102, 233] }`.
+/// Used with `#[serde(with = "lossless_path")]`
+pub mod lossless_path {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::os_str_bytes;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Utf8(String),
+        Bytes { display: String, bytes: Vec<u8> },
+    }
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match path.to_str() {
+            Some(path) => Repr::Utf8(path.to_string()),
+            None => Repr::Bytes {
+                display: path.to_string_lossy().into_owned(),
+                bytes: os_str_bytes(path.as_os_str()).into_owned(),
+            },
+        };
+        repr.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Utf8(path) => PathBuf::from(path),
+            Repr::Bytes { bytes, .. } => path_from_bytes(bytes),
+        })
+    }
+
+    #[cfg(unix)]
+    fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+        use std::os::unix::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    }
+
+    #[cfg(not(unix))]
+    fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+        PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roots_inside_another_root_are_dropped() {
+        let root = tempfile::tempdir().unwrap();
+        let (work, project) = (root.path().join("work"), root.path().join("work").join("project"));
+        let other = root.path().join("other");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir(&other).unwrap();
+
+        let (kept, dropped) = dedupe_roots(&[project.clone(), work.clone(), other.clone()]);
+
+        assert_eq!(kept, vec![work.clone(), other]);
+        assert_eq!(dropped, vec![(project, work)]);
+    }
+
+    #[test]
+    fn first_of_identical_roots_is_kept() {
+        let root = tempfile::tempdir().unwrap();
+        let work = root.path().join("work");
+        std::fs::create_dir(&work).unwrap();
+        std::fs::create_dir(root.path().join("other")).unwrap();
+        let same_work = root.path().join("other").join("..").join("work");
+
+        let (kept, dropped) = dedupe_roots(&[work.clone(), same_work.clone()]);
+
+        assert_eq!(kept, vec![work.clone()]);
+        assert_eq!(dropped, vec![(same_work, work)]);
+    }
+
+    #[test]
+    fn roots_sharing_a_name_prefix_are_kept() {
+        let (kept, dropped) = dedupe_roots(&[PathBuf::from("/nonexistent/work"), PathBuf::from("/nonexistent/work2")]);
+
+        assert_eq!(kept.len(), 2);
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    fn common_ancestor_is_the_deepest_shared_directory() {
+        let paths = |paths: &[&str]| paths.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+        assert_eq!(common_ancestor(&paths(&["/work/a/src", "/work/a/target", "/work/b"])), PathBuf::from("/work"));
+        assert_eq!(common_ancestor(&paths(&["/work/a", "/work/ab"])), PathBuf::from("/work"));
+        assert_eq!(common_ancestor(&paths(&["/work/a"])), PathBuf::from("/work/a"));
+        assert_eq!(common_ancestor(&paths(&["/work", "/srv"])), PathBuf::from("/"));
+        assert_eq!(common_ancestor(&[]), PathBuf::new());
+    }
+
+    #[test]
+    fn variables_and_home_are_expanded() {
+        std::env::set_var("WIPER_TEST_EXPAND", "/srv/data");
+        let home_dir = Some(Path::new("/home/alice"));
+
+        assert_eq!(expand_path("~", home_dir), PathBuf::from("/home/alice"));
+        assert_eq!(expand_path("~/work", home_dir), PathBuf::from("/home/alice/work"));
+        assert_eq!(expand_path("$WIPER_TEST_EXPAND/cache", home_dir), PathBuf::from("/srv/data/cache"));
+        assert_eq!(expand_path("${WIPER_TEST_EXPAND}2", home_dir), PathBuf::from("/srv/data2"));
+        assert_eq!(expand_path("~/work", None), PathBuf::from("~/work"));
+    }
+
+    #[test]
+    fn unexpandable_parts_are_kept_as_written() {
+        let home_dir = Some(Path::new("/home/alice"));
+
+        assert_eq!(expand_path("~bob/work", home_dir), PathBuf::from("~bob/work"));
+        assert_eq!(expand_path("/work/${WIPER_TEST_EXPAND", home_dir), PathBuf::from("/work/${WIPER_TEST_EXPAND"));
+        assert_eq!(expand_path("/work/$WIPER_TEST_UNSET/a", home_dir), PathBuf::from("/work/$WIPER_TEST_UNSET/a"));
+        assert_eq!(expand_path("/work/${WIPER_TEST_UNSET}", home_dir), PathBuf::from("/work/${WIPER_TEST_UNSET}"));
+        assert_eq!(expand_path("/work/$", home_dir), PathBuf::from("/work/$"));
+        assert_eq!(expand_path("/work/a~", home_dir), PathBuf::from("/work/a~"));
+    }
+
+    #[test]
+    fn completion_extends_to_the_longest_common_prefix() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["project-api", "project-app", "other"] {
+            std::fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        std::fs::write(root.path().join("project-file"), "").unwrap();
+        let input = |input: &str| format!("{}{}{}", root.path().display(), MAIN_SEPARATOR, input);
+
+        let completion = complete_directory(&input("pro"), None);
+        assert_eq!(completion.text, input("project-ap"));
+        assert_eq!(completion.candidates, vec!["project-api", "project-app"]);
+
+        let completion = complete_directory(&input("ot"), None);
+        assert_eq!(completion.text, input(&format!("other{}", MAIN_SEPARATOR)));
+
+        let completion = complete_directory(&input("none"), None);
+        assert_eq!(completion.text, input("none"));
+        assert!(completion.candidates.is_empty());
+    }
+
+    #[test]
+    fn hidden_directories_are_only_completed_from_a_dot() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [".cache", ".config", "cargo"] {
+            std::fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        let input = |input: &str| format!("{}{}{}", root.path().display(), MAIN_SEPARATOR, input);
+
+        assert_eq!(complete_directory(&input(""), None).candidates, vec!["cargo"]);
+        let completion = complete_directory(&input("."), None);
+        assert_eq!(completion.candidates, vec![".cache", ".config"]);
+        assert_eq!(completion.text, input(".c"));
+    }
+}
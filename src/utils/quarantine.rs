@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::utils::os_path::{canonicalize_parent, lossless_path};
+
+/// Name of the quarantined entry inside its slot, next to the manifest
+const CONTENT_NAME: &str = "content";
+const MANIFEST_FILE_NAME: &str = "manifest.toml";
+
+/// Where a quarantined entry comes from, saved next to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Exact even when it is not valid UTF-8
+    #[serde(with = "lossless_path")]
+    pub original_path: PathBuf,
+    pub size: u64,
+    pub file_count: usize,
+    pub quarantined_at: DateTime<Local>,
+}
+
+/// Entry moved into its own slot of the quarantine directory, the id being the name of the slot
+#[derive(Debug, Clone)]
+pub struct QuarantinedEntry {
+    pub id: String,
+    pub manifest: Manifest,
+}
+
+/// Move `path`, holding `file_count` files of `size` bytes, into a new slot of `quarantine_dir`,
+/// returning the id to restore it. A link is moved itself, not its target
+pub fn quarantine(path: &Path, file_count: usize, size: u64, quarantine_dir: &Path) -> io::Result<String> {
+    let original_path = canonicalize_parent(path)?;
+    let manifest = Manifest { original_path, size, file_count, quarantined_at: Local::now() };
+
+    fs::create_dir_all(quarantine_dir)?;
+    let (id, slot) = create_slot(quarantine_dir, &manifest.quarantined_at)?;
+
+    // the manifest comes first so that an interrupted move can still be found
+    let moved = write_manifest(&slot, &manifest).and_then(|()| move_path(&manifest.original_path, &slot.join(CONTENT_NAME)));
+    if let Err(e) = moved {
+        let _ = fs::remove_dir_all(&slot);
+        return Err(e);
+    }
+
+    Ok(id)
+}
+
+/// Create an empty slot named after `time`, suffixed to be unique
+fn create_slot(quarantine_dir: &Path, time: &DateTime<Local>) -> io::Result<(String, PathBuf)> {
+    let prefix = time.format("%Y%m%d-%H%M%S");
+    for n in 0.. {
+        let id = format!("{}-{}", prefix, n);
+        let slot = quarantine_dir.join(&id);
+        match fs::create_dir(&slot) {
+            Ok(()) => return Ok((id, slot)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+fn write_manifest(slot: &Path, manifest: &Manifest) -> io::Result<()> {
+    let content = toml::to_string(manifest).map_err(io::Error::other)?;
+    fs::write(slot.join(MANIFEST_FILE_NAME), content)
+}
+
+fn read_manifest(slot: &Path) -> io::Result<Manifest> {
+    let content = fs::read_to_string(slot.join(MANIFEST_FILE_NAME))?;
+    toml::from_str(&content).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Rename `from` to `to`, copying then removing it when they are on different filesystems
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            copy_recursive(from, to)?;
+            if from.symlink_metadata()?.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            }
+        },
+        result => result,
+    }
+}
+
+/// Copy the file, link or directory `from` to `to`, which must not exist. Links are copied as links
+pub fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    // the walk follows a root link
+    if from.symlink_metadata()?.file_type().is_symlink() {
+        return copy_link(from, to);
+    }
+
+    for entry in WalkDir::new(from) {
+        let entry = entry.map_err(io::Error::other)?;
+        // joining an empty path would add a trailing separator, which fails on a file
+        let relative_path = entry.path().strip_prefix(from).map_err(io::Error::other)?;
+        let target = if relative_path.as_os_str().is_empty() { to.to_path_buf() } else { to.join(relative_path) };
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir(&target)?;
+        } else if file_type.is_symlink() {
+            copy_link(entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_link(from: &Path, to: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    }
+    #[cfg(not(unix))]
+    {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Entries of the quarantine directory, oldest first. Slots without a valid manifest are skipped
+pub fn list(quarantine_dir: &Path) -> io::Result<Vec<QuarantinedEntry>> {
+    let read_dir = match fs::read_dir(quarantine_dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    let mut entries = vec![];
+    for slot in read_dir {
+        let slot = slot?;
+        let Ok(id) = slot.file_name().into_string() else { continue; };
+        match read_manifest(&slot.path()) {
+            Ok(manifest) => entries.push(QuarantinedEntry { id, manifest }),
+            Err(e) => warn!("Skipping the quarantine slot {}: {}", slot.path().display(), e),
+        }
+    }
+    entries.sort_by_key(|entry| entry.manifest.quarantined_at);
+
+    Ok(entries)
+}
+
+/// Move the entry `id` back to its original path, which must not exist again
+pub fn restore(quarantine_dir: &Path, id: &str) -> io::Result<PathBuf> {
+    let slot = quarantine_dir.join(id);
+    let manifest = read_manifest(&slot).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => io::Error::new(ErrorKind::NotFound, format!("no quarantined entry {} in {}", id, quarantine_dir.display())),
+        _ => e,
+    })?;
+
+    let original_path = manifest.original_path;
+    if original_path.symlink_metadata().is_ok() {
+        return Err(io::Error::new(ErrorKind::AlreadyExists, format!("{} already exists", original_path.display())));
+    }
+    if let Some(parent) = original_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    move_path(&slot.join(CONTENT_NAME), &original_path)?;
+    fs::remove_dir_all(&slot)?;
+
+    Ok(original_path)
+}
+
+/// Restore the most recently quarantined entry of each path, returning the number of restored paths.
+/// Paths no longer in the quarantine are skipped
+pub fn restore_paths(quarantine_dir: &Path, paths: &[PathBuf]) -> io::Result<usize> {
+    let paths = paths.iter().collect::<HashSet<_>>();
+    let mut ids = HashMap::new();
+    // oldest first, the most recent entry of a path wins
+    for entry in list(quarantine_dir)? {
+        if paths.contains(&entry.manifest.original_path) {
+            ids.insert(entry.manifest.original_path, entry.id);
+        }
+    }
+
+    for id in ids.values() {
+        restore(quarantine_dir, id)?;
+    }
+    Ok(ids.len())
+}
+
+/// Delete the entries quarantined for longer than `older_than`, or only list them on a dry run
+pub fn purge(quarantine_dir: &Path, older_than: chrono::Duration, dry_run: bool) -> io::Result<Vec<QuarantinedEntry>> {
+    let limit = Local::now() - older_than;
+    let expired = list(quarantine_dir)?
+        .into_iter()
+        .filter(|entry| entry.manifest.quarantined_at < limit)
+        .collect::<Vec<_>>();
+
+    if !dry_run {
+        for entry in &expired {
+            fs::remove_dir_all(quarantine_dir.join(&entry.id))?;
+        }
+    }
+
+    Ok(expired)
+}
+
+/// Parse an age such as `30m`, `12h`, `7d` or `2w`
+pub fn parse_age(age: &str) -> Result<chrono::Duration, String> {
+    let unit_start = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (count, unit) = age.split_at(unit_start);
+    let count = count.parse::<i64>().map_err(|_| format!("invalid age {}, expected e.g. 7d", age))?;
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(count)),
+        "m" => Ok(chrono::Duration::minutes(count)),
+        "h" => Ok(chrono::Duration::hours(count)),
+        "d" => Ok(chrono::Duration::days(count)),
+        "w" => Ok(chrono::Duration::weeks(count)),
+        _ => Err(format!("invalid unit in {}, expected s, m, h, d or w", age)),
+    }
+}
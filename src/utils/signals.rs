@@ -0,0 +1,78 @@
+use std::sync::{Arc, OnceLock, atomic::{AtomicBool, AtomicUsize, Ordering}};
+
+use eyre::{Result, WrapErr};
+use signal_hook::consts::{SIGINT, SIGTERM};
+#[cfg(unix)]
+use signal_hook::consts::{SIGHUP, SIGTSTP};
+
+/// Signal received by the process, handled at the next iteration of the main loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGINT, SIGTERM or SIGHUP
+    Terminate(i32),
+    /// SIGTSTP
+    Suspend,
+}
+
+/// Record the termination and suspension signals instead of letting them kill or stop the process
+/// with the terminal left in raw mode
+pub struct Signals {
+    /// Last signal received, 0 if none
+    received: Arc<AtomicUsize>,
+}
+
+impl Signals {
+    pub fn register() -> Result<Self> {
+        let received = Arc::new(AtomicUsize::new(0));
+
+        #[cfg(unix)]
+        let (terminate_signals, signals) = ([SIGINT, SIGTERM, SIGHUP], [SIGINT, SIGTERM, SIGHUP, SIGTSTP]);
+        #[cfg(not(unix))]
+        let (terminate_signals, signals) = ([SIGINT, SIGTERM], [SIGINT, SIGTERM]);
+
+        for signal in signals {
+            signal_hook::flag::register_usize(signal, received.clone(), signal as usize)
+                .wrap_err_with(|| format!("Unable to handle signal {}", signal_name(signal)))?;
+        }
+        for signal in terminate_signals {
+            signal_hook::flag::register(signal, terminating_flag().clone())
+                .wrap_err_with(|| format!("Unable to handle signal {}", signal_name(signal)))?;
+        }
+
+        Ok(Self { received })
+    }
+
+    /// The signal received since the last call, if any
+    pub fn take(&self) -> Option<Signal> {
+        match self.received.swap(0, Ordering::Relaxed) as i32 {
+            0 => None,
+            #[cfg(unix)]
+            SIGTSTP => Some(Signal::Suspend),
+            signal => Some(Signal::Terminate(signal)),
+        }
+    }
+}
+
+fn terminating_flag() -> &'static Arc<AtomicBool> {
+    static TERMINATING: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    TERMINATING.get_or_init(Arc::default)
+}
+
+/// Whether a termination signal was received, for the walks to stop without waiting for the main loop.
+/// A deletion in progress is still completed, so that its audit records are written
+pub fn is_terminating() -> bool {
+    terminating_flag().load(Ordering::Relaxed)
+}
+
+pub fn signal_name(signal: i32) -> String {
+    signal_hook::low_level::signal_name(signal)
+        .map(String::from)
+        .unwrap_or_else(|| signal.to_string())
+}
+
+/// Stop the process the way SIGTSTP does by default, returning once it is resumed
+pub fn suspend_process() -> Result<()> {
+    #[cfg(unix)]
+    signal_hook::low_level::emulate_default_handler(SIGTSTP).wrap_err("Unable to suspend the process")?;
+    Ok(())
+}
@@ -1,43 +1,134 @@
 use ratatui::widgets::{ListState, ScrollbarState};
 
+/// Cursor movements shared by every list displayed with a `ListState`
+pub trait Navigable {
+    /// Number of displayed rows
+    fn len(&self) -> usize;
+
+    fn list_state(&self) -> &ListState;
+
+    fn list_state_mut(&mut self) -> &mut ListState;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn next(&mut self) {
+        if self.is_empty() { return; }
+        let selected = self.list_state().selected()
+            .map(|i| if i >= self.len() - 1 { 0 } else { i + 1 });
+        self.list_state_mut().select(selected);
+    }
+
+    fn previous(&mut self) {
+        if self.is_empty() { return; }
+        let selected = self.list_state().selected()
+            .map(|i| if i == 0 { self.len() - 1 } else { i - 1 });
+        self.list_state_mut().select(selected);
+    }
+
+    /// Move the selection `count` rows down, stopping at the last row
+    fn forward(&mut self, count: usize) {
+        if self.is_empty() { return; }
+        let last = self.len() - 1;
+        let selected = self.list_state().selected().map_or(0, |i| (i + count).min(last));
+        self.list_state_mut().select(Some(selected));
+    }
+
+    /// Move the selection `count` rows up, stopping at the first row
+    fn backward(&mut self, count: usize) {
+        if self.is_empty() { return; }
+        let selected = self.list_state().selected().map_or(0, |i| i.saturating_sub(count));
+        self.list_state_mut().select(Some(selected));
+    }
+
+    fn first(&mut self) {
+        let selected = (!self.is_empty()).then_some(0);
+        self.list_state_mut().select(selected);
+    }
+
+    fn last(&mut self) {
+        let selected = self.len().checked_sub(1);
+        self.list_state_mut().select(selected);
+    }
+
+    fn get_scrollbar_state(&self) -> ScrollbarState {
+        ScrollbarState::default()
+        .position(self.list_state().selected().unwrap_or(0) as u16)
+        .content_length(self.len() as u16)
+    }
+}
+
 #[derive(Clone)]
 pub struct StatefulList<T> {
+    /// Selection state, expressed as a position among the visible items
     pub state: ListState,
     pub items: Vec<T>,
+    /// Indices of the visible items when the list is narrowed, all items are visible otherwise
+    pub visible: Option<Vec<usize>>,
 }
 
 impl<T> StatefulList<T> {
     pub fn set_items(&mut self, items: Vec<T>) {
         self.state.select((!items.is_empty()).then_some(0));
         self.items = items;
+        self.visible = None;
+    }
+
+    /// Narrow the list to the given item indices, keeping the selected item if it is still visible
+    pub fn set_visible(&mut self, visible: Option<Vec<usize>>) {
+        let selected_index = self.selected_index();
+        self.visible = visible;
+        self.state.select((self.visible_len() > 0).then_some(0));
+        if let Some(idx) = selected_index {
+            self.select_index(idx);
+        }
+    }
+
+    pub fn visible_len(&self) -> usize {
+        self.visible.as_ref().map_or(self.items.len(), |visible| visible.len())
+    }
+
+    /// Item index of the visible row at `position`
+    pub fn visible_index(&self, position: usize) -> usize {
+        self.visible.as_ref().map_or(position, |visible| visible[position])
     }
 
-    pub fn next(&mut self) {
-        if self.items.is_empty() { return; }
-        self.state.select(
-            self.state.selected()
-            .map(|i| if i >= self.items.len() - 1 { 0 } else { i + 1 })
-        );
+    pub fn visible_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.visible_len()).map(|position| self.visible_index(position))
     }
 
-    pub fn previous(&mut self) {
-        if self.items.is_empty() { return; }
-        self.state.select(
-            self.state.selected()
-            .map(|i| if i <= 0 { self.items.len() - 1 } else { i - 1 })
-        );
+    /// Item index of the selected row
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state.selected().map(|position| self.visible_index(position))
+    }
+
+    /// Select the row showing the item `idx`, does nothing if it is not visible
+    pub fn select_index(&mut self, idx: usize) {
+        let position = self.visible_indices().position(|i| i == idx);
+        if position.is_some() {
+            self.state.select(position);
+        }
     }
 
     pub fn unselect(&mut self) {
         self.state.select(None);
     }
 
-    pub fn get_scrollbar_state(&self) -> ScrollbarState {
-        ScrollbarState::default()
-        .position(self.state.selected().unwrap_or(0) as u16)
-        .content_length(self.items.len() as u16)
+}
+
+impl<T> Navigable for StatefulList<T> {
+    fn len(&self) -> usize {
+        self.visible_len()
+    }
+
+    fn list_state(&self) -> &ListState {
+        &self.state
     }
 
+    fn list_state_mut(&mut self) -> &mut ListState {
+        &mut self.state
+    }
 }
 
 impl<T> Default for StatefulList<T> {
@@ -45,6 +136,7 @@ impl<T> Default for StatefulList<T> {
         Self {
             state: ListState::default(),
             items: Vec::new(),
+            visible: None,
         }
     }
 }
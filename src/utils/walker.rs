@@ -1,7 +1,32 @@
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
+use std::path::PathBuf;
 
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display as StrumDisplay, EnumString};
 use walkdir::WalkDir;
 
+use crate::utils::audit::{self, AuditRecord};
+use crate::utils::guard::{DeletionGuard, mount_points};
+use crate::utils::os_path::{canonicalize_parent, tilde_path};
+use crate::utils::quarantine;
+use crate::utils::signals::is_terminating;
+
+/// How entries are removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, StrumDisplay, EnumString, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum DeletionStrategy {
+    /// Move entries to the system trash
+    #[default]
+    Trash,
+    /// Remove entries from the disk
+    Permanent,
+    /// Move entries to the quarantine directory, from which they are purged later
+    Quarantine,
+}
+
 pub fn count_and_size(path: impl AsRef<std::path::Path>) -> (usize, u64) {
     WalkDir::new(path)
         .into_iter()
@@ -13,6 +38,31 @@ pub fn count_and_size(path: impl AsRef<std::path::Path>) -> (usize, u64) {
         })
 }
 
+/// What an entry holds, measured in a single walk
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryContent {
+    pub file_count: usize,
+    pub size: u64,
+    /// First `.git` directory found in the entry, the entry itself included
+    pub git_dir: Option<PathBuf>,
+}
+
+/// Count and size the files of `path` as `count_and_size` does, looking for a `.git` directory on the way.
+/// Stops early when the process is terminating
+pub fn measure_content(path: impl AsRef<std::path::Path>) -> EntryContent {
+    let mut content = EntryContent::default();
+    for entry in WalkDir::new(path).into_iter().take_while(|_| !is_terminating()).filter_map(|e| e.ok()) {
+        if content.git_dir.is_none() && entry.file_type().is_dir() && entry.file_name() == OsStr::new(".git") {
+            content.git_dir = Some(entry.path().to_path_buf());
+        }
+        if let Some(metadata) = entry.metadata().ok().filter(|metadata| metadata.is_file()) {
+            content.file_count += 1;
+            content.size += metadata.len();
+        }
+    }
+    content
+}
+
 pub fn is_hidden(entry: &walkdir::DirEntry) -> bool {
     entry
         .file_name()
@@ -32,9 +82,22 @@ where
     })
 }
 
+/// Whether `entry` is one of the `excluded` canonical directories.
+/// Names are compared first so that only the entries named like one of them are resolved
+fn is_excluded(entry: &walkdir::DirEntry, excluded: &[PathBuf]) -> bool {
+    entry.file_type().is_dir()
+        && excluded.iter().any(|dir| {
+            dir.file_name() == Some(entry.file_name()) && entry.path().canonicalize().is_ok_and(|path| &path == dir)
+        })
+}
+
+/// List the entries whose file name matches the predicate, the `excluded` canonical directories not being searched.
+/// When `prune` is set the content of matched directories is not searched. Stops early when the process is terminating
 pub fn get_dir_list_from_path<'a, P, F>(
     path: &P,
     filter_filename_predicate: &'a F,
+    prune: bool,
+    excluded: &'a [PathBuf],
 ) -> impl Iterator<Item = walkdir::DirEntry> + 'a
 where
     P: AsRef<std::path::Path>,
@@ -43,14 +106,178 @@ where
     WalkDir::new(path)
         .follow_links(true)
         .into_iter()
-        .filter_entry(|entry| filter_entry_parent_from_predicate(entry, filter_filename_predicate))
+        .filter_entry(move |entry| {
+            !is_excluded(entry, excluded) && (!prune || filter_entry_parent_from_predicate(entry, filter_filename_predicate))
+        })
+        .take_while(|_| !is_terminating())
         .filter_map(|e| e.ok())
         // .filter(|entry| entry.file_type().is_dir())
         .filter(|entry| filter_filename_predicate(entry.file_name()))
 }
 
 
-pub fn delete_entries(entries: &[walkdir::DirEntry]) -> Result<(), std::io::Error> {
-    trash::delete_all(entries.iter().map(|entry| entry.path())).unwrap();
-    Ok(())
-}
\ No newline at end of file
+/// Where the entries removed by `delete_entries` go and are recorded
+#[derive(Debug, Clone, Default)]
+pub struct DeletionSettings {
+    /// Directory the quarantine deletion strategy moves the entries to
+    pub quarantine_dir: PathBuf,
+    /// JSON-lines file every deletion is appended to
+    pub audit_log: PathBuf,
+    /// Critical paths which are never deleted
+    pub guard: DeletionGuard,
+}
+
+/// Entry to delete with its content as measured by the scan, so that it is not walked again
+#[derive(Debug, Clone)]
+pub struct DeletionTarget {
+    pub entry: walkdir::DirEntry,
+    pub file_count: usize,
+    pub size: u64,
+    /// First `.git` directory found in the entry by the scan
+    pub git_dir: Option<PathBuf>,
+}
+
+impl DeletionTarget {
+    pub fn new(entry: walkdir::DirEntry, content: EntryContent) -> Self {
+        Self { entry, file_count: content.file_count, size: content.size, git_dir: content.git_dir }
+    }
+}
+
+/// Outcome of `delete_entries`
+#[derive(Debug, Default)]
+pub struct DeletionReport {
+    /// Paths of the removed entries with their parent canonical, a link being removed rather than its target, in order
+    pub deleted: Vec<PathBuf>,
+    /// Why the deletion stopped, the entries after the failing one being left untouched
+    pub error: Option<std::io::Error>,
+    /// First failure to append to the audit log, the deletions being done anyway
+    pub audit_error: Option<std::io::Error>,
+}
+
+/// Remove the targets with `strategy` in order, stopping at the first failure.
+/// Nothing is removed if one of the targets is protected by the guard, which relies on the `.git` directories found by the scan.
+/// Each attempt is appended to the audit log, a log which cannot be written being reported without stopping the deletion
+pub fn delete_entries(targets: &[DeletionTarget], strategy: DeletionStrategy, settings: &DeletionSettings) -> DeletionReport {
+    let mut report = DeletionReport::default();
+
+    let mount_points = mount_points();
+    let refused = targets
+        .iter()
+        .filter_map(|target| {
+            settings.guard
+                .check(target.entry.path(), target.git_dir.as_deref(), &mount_points)
+                .map(|reason| (target.entry.path(), reason))
+        })
+        .collect::<Vec<_>>();
+    if !refused.is_empty() {
+        for (path, reason) in &refused {
+            warn!("Refusing to delete {}: {}", path.display(), reason);
+            // protected entries can be whole file systems, they are not measured
+            let canonical_path = canonicalize_parent(path).unwrap_or_else(|_| path.to_path_buf());
+            report.append_audit_record(settings, AuditRecord::new(canonical_path, 0, 0, strategy).refused(reason));
+        }
+        let message = refusal_message(&refused, settings.guard.home_dir.as_deref());
+        report.error = Some(std::io::Error::new(std::io::ErrorKind::PermissionDenied, message));
+        return report;
+    }
+
+    for target in targets {
+        // the trash records the path with its parent canonical, which cannot be resolved once deleted
+        let path = canonicalize_parent(target.entry.path()).unwrap_or_else(|_| target.entry.path().to_path_buf());
+        let mut record = AuditRecord::new(path.clone(), target.file_count, target.size, strategy);
+
+        match delete_entry(target, strategy, &settings.quarantine_dir) {
+            Ok(quarantine_id) => {
+                record.quarantine_id = quarantine_id;
+                report.append_audit_record(settings, record);
+                report.deleted.push(path);
+            },
+            Err(e) => {
+                report.append_audit_record(settings, record.failed(&e));
+                report.error = Some(e);
+                break;
+            },
+        }
+    }
+    report
+}
+
+impl DeletionReport {
+    fn append_audit_record(&mut self, settings: &DeletionSettings, record: AuditRecord) {
+        if let Err(e) = audit::append(&settings.audit_log, &record) {
+            warn!("Unable to write the audit log {}: {}", settings.audit_log.display(), e);
+            self.audit_error.get_or_insert(e);
+        }
+    }
+}
+
+/// Reasons of the first refused entries, e.g. `refusing to delete ~ (it is the home directory), nothing was deleted`
+fn refusal_message(refused: &[(&std::path::Path, String)], home_dir: Option<&std::path::Path>) -> String {
+    const MAX_LISTED: usize = 3;
+
+    let mut message = refused
+        .iter()
+        .take(MAX_LISTED)
+        .map(|(path, reason)| format!("{} ({})", tilde_path(path, home_dir), reason))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if refused.len() > MAX_LISTED {
+        message.push_str(&format!(" and {} others", refused.len() - MAX_LISTED));
+    }
+    format!("refusing to delete {}, nothing was deleted", message)
+}
+
+/// Remove a target, returning its id in the quarantine
+fn delete_entry(target: &DeletionTarget, strategy: DeletionStrategy, quarantine_dir: &std::path::Path) -> Result<Option<String>, std::io::Error> {
+    let entry = &target.entry;
+    match strategy {
+        DeletionStrategy::Trash => {
+            trash::delete(entry.path())
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+        },
+        DeletionStrategy::Permanent => {
+            if entry.file_type().is_dir() {
+                std::fs::remove_dir_all(entry.path())?;
+            } else {
+                std::fs::remove_file(entry.path())?;
+            }
+        },
+        DeletionStrategy::Quarantine => {
+            let id = quarantine::quarantine(entry.path(), target.file_count, target.size, quarantine_dir)?;
+            info!("Quarantined {} as {}", entry.path().display(), id);
+            return Ok(Some(id));
+        },
+    }
+    Ok(None)
+}
+
+/// Move the trashed `paths` back to their original location, the most recent item being restored
+/// when a path was trashed several times. Returns the number of restored paths, those no longer
+/// in the trash being skipped
+#[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+pub fn restore_from_trash(paths: &[PathBuf]) -> Result<usize, std::io::Error> {
+    let paths = paths.iter().collect::<HashSet<_>>();
+    let mut items = HashMap::new();
+    for item in trash::os_limited::list().map_err(|e| std::io::Error::other(e.to_string()))? {
+        let path = item.original_path();
+        if !paths.contains(&path) {
+            continue;
+        }
+        match items.get(&path) {
+            Some(trash::TrashItem { time_deleted, .. }) if *time_deleted >= item.time_deleted => {},
+            _ => {
+                items.insert(path, item);
+            },
+        }
+    }
+
+    let restored_count = items.len();
+    trash::os_limited::restore_all(items.into_values())
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(restored_count)
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))))]
+pub fn restore_from_trash(_paths: &[PathBuf]) -> Result<usize, std::io::Error> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "restoring from the trash is not supported on this platform"))
+}
@@ -0,0 +1,108 @@
+//! Every deletion must be read back from the audit log as it was recorded
+
+use std::fs;
+use std::path::PathBuf;
+
+use wiper::utils::audit::{self, AuditFilter, AuditRecord, AuditResult};
+use wiper::utils::walker::DeletionStrategy;
+
+fn record(path: &str) -> AuditRecord {
+    AuditRecord::new(PathBuf::from(path), 2, 1024, DeletionStrategy::Trash)
+}
+
+#[test]
+fn records_are_read_back_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let audit_log = dir.path().join("state").join("audit.jsonl");
+
+    audit::append(&audit_log, &record("/work/a/node_modules")).unwrap();
+    audit::append(&audit_log, &record("/work/b/node_modules").failed(&std::io::Error::other("busy"))).unwrap();
+
+    let records = audit::read(&audit_log).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].path, PathBuf::from("/work/a/node_modules"));
+    assert_eq!(records[0].file_count, 2);
+    assert_eq!(records[0].size, 1024);
+    assert_eq!(records[0].strategy, DeletionStrategy::Trash);
+    assert_eq!(records[0].result, AuditResult::Ok);
+    assert_eq!(records[1].result, AuditResult::Failed);
+    assert_eq!(records[1].error.as_deref(), Some("busy"));
+}
+
+#[test]
+fn missing_log_has_no_records() {
+    let dir = tempfile::tempdir().unwrap();
+
+    assert!(audit::read(&dir.path().join("audit.jsonl")).unwrap().is_empty());
+}
+
+#[test]
+fn invalid_lines_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let audit_log = dir.path().join("audit.jsonl");
+    audit::append(&audit_log, &record("/work/a/node_modules")).unwrap();
+    fs::write(&audit_log, fs::read_to_string(&audit_log).unwrap() + "not json\n\n").unwrap();
+    audit::append(&audit_log, &record("/work/b/node_modules")).unwrap();
+
+    let records = audit::read(&audit_log).unwrap();
+
+    assert_eq!(records.iter().map(|record| record.path.clone()).collect::<Vec<_>>(), vec![
+        PathBuf::from("/work/a/node_modules"),
+        PathBuf::from("/work/b/node_modules"),
+    ]);
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_paths_are_recorded_exactly() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let audit_log = dir.path().join("audit.jsonl");
+    let path = PathBuf::from(OsStr::from_bytes(b"/work/caf\xe9"));
+
+    audit::append(&audit_log, &AuditRecord::new(path.clone(), 1, 1, DeletionStrategy::Permanent)).unwrap();
+
+    assert_eq!(audit::read(&audit_log).unwrap()[0].path, path);
+    assert!(fs::read_to_string(&audit_log).unwrap().contains("caf\u{FFFD}"));
+}
+
+#[test]
+fn filter_matches_each_criterion() {
+    let mut old = record("/work/a/node_modules");
+    old.timestamp -= chrono::Duration::days(10);
+    old.user = String::from("alice");
+    let mut failed = record("/work/b/target").failed(&std::io::Error::other("busy"));
+    failed.user = String::from("bob");
+    let mut refused = record("/").refused("it is the filesystem root");
+    refused.user = String::from("root");
+    refused.sudo_user = Some(String::from("alice"));
+    let records = [old, failed, refused];
+
+    let matching = |filter: AuditFilter| {
+        records.iter().filter(|record| filter.matches(record)).map(|record| record.path.clone()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(matching(AuditFilter::default()).len(), 3);
+    assert_eq!(matching(AuditFilter { since: Some(chrono::Duration::days(7)), ..Default::default() }).len(), 2);
+    assert_eq!(matching(AuditFilter { path: Some(String::from("target")), ..Default::default() }), vec![PathBuf::from("/work/b/target")]);
+    assert_eq!(matching(AuditFilter { user: Some(String::from("alice")), ..Default::default() }), vec![PathBuf::from("/work/a/node_modules"), PathBuf::from("/")]);
+    assert_eq!(matching(AuditFilter { user: Some(String::from("root")), ..Default::default() }), vec![PathBuf::from("/")]);
+    assert_eq!(matching(AuditFilter { failed_only: true, ..Default::default() }), vec![PathBuf::from("/work/b/target"), PathBuf::from("/")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn user_is_not_read_from_the_environment() {
+    std::env::set_var("USER", "spoofed");
+    std::env::set_var("SUDO_USER", "alice");
+
+    let record = record("/work/a/node_modules");
+
+    assert_ne!(record.user, "spoofed");
+    assert!(!record.user.is_empty());
+    assert_eq!(record.sudo_user.as_deref(), Some("alice"));
+    let json = serde_json::to_string(&record).unwrap();
+    assert_eq!(serde_json::from_str::<AuditRecord>(&json).unwrap().sudo_user.as_deref(), Some("alice"));
+}
@@ -0,0 +1,67 @@
+//! The browser measures the directories off the interface thread
+use std::fs;
+use std::path::Path;
+
+use wiper::app::browse::BrowseState;
+
+fn names(browse: &BrowseState) -> Vec<String> {
+    browse.entries.items.iter().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect()
+}
+
+fn highlighted(browse: &BrowseState) -> Option<&Path> {
+    browse.entries.selected_index().map(|idx| browse.entries.items[idx].path())
+}
+
+#[test]
+fn children_are_listed_by_decreasing_size() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("small")).unwrap();
+    fs::create_dir_all(root.path().join("large").join("nested")).unwrap();
+    fs::write(root.path().join("small").join("file"), [0; 10]).unwrap();
+    fs::write(root.path().join("large").join("nested").join("file"), [0; 100]).unwrap();
+
+    let mut browse = BrowseState::new(root.path());
+    browse.wait();
+
+    assert_eq!(names(&browse), vec!["large", "small"]);
+    assert_eq!(browse.entries_size, vec![100, 10]);
+    assert_eq!(browse.entries_file_count, vec![1, 1]);
+}
+
+#[test]
+fn going_up_highlights_the_directory_left() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("large")).unwrap();
+    fs::create_dir_all(root.path().join("small")).unwrap();
+    fs::write(root.path().join("large").join("file"), [0; 100]).unwrap();
+    let mut browse = BrowseState::new(root.path());
+    browse.wait();
+
+    browse.entries.select_index(1);
+    browse.enter_selected();
+    assert!(browse.entries.items.is_empty());
+    browse.wait();
+    browse.go_to_parent();
+    browse.wait();
+
+    assert_eq!(highlighted(&browse), Some(browse.root.join("small").as_path()));
+}
+
+#[test]
+fn refreshing_keeps_the_highlighted_entry() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("first")).unwrap();
+    fs::create_dir_all(root.path().join("second")).unwrap();
+    fs::write(root.path().join("first").join("file"), [0; 10]).unwrap();
+    let mut browse = BrowseState::new(root.path());
+    browse.wait();
+    browse.entries.select_index(1);
+
+    // the second directory becomes the largest one
+    fs::write(root.path().join("second").join("file"), [0; 100]).unwrap();
+    browse.refresh();
+    browse.wait();
+
+    assert_eq!(names(&browse), vec!["second", "first"]);
+    assert_eq!(highlighted(&browse), Some(browse.root.join("second").as_path()));
+}
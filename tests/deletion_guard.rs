@@ -0,0 +1,141 @@
+//! Critical paths must be refused whatever the way they are reached
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use wiper::app::App;
+use wiper::config::{Config, ConfigLayer, ConfigSource};
+use wiper::utils::guard::{DeletionGuard, mount_points};
+use wiper::utils::walker::{DeletionStrategy, measure_content};
+
+/// Check `path` with its content measured as the scan does
+fn check(guard: &DeletionGuard, path: &Path) -> Option<String> {
+    guard.check(path, measure_content(path).git_dir.as_deref(), &mount_points())
+}
+
+fn assert_refused(guard: &DeletionGuard, path: &Path, reason: &str) {
+    let refusal = check(guard, path);
+    assert!(
+        refusal.as_deref().is_some_and(|refusal| refusal.contains(reason)),
+        "{} refused with {:?}, expected {}", path.display(), refusal, reason
+    );
+}
+
+#[test]
+fn filesystem_root_is_refused() {
+    // not measured, which would walk the whole filesystem
+    let refusal = DeletionGuard::default().check(Path::new("/"), None, &mount_points());
+    assert!(refusal.is_some_and(|refusal| refusal.contains("filesystem root")));
+}
+
+#[test]
+fn home_directory_and_its_ancestors_are_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let home_dir = dir.path().join("home");
+    fs::create_dir_all(home_dir.join("project")).unwrap();
+    let guard = DeletionGuard { home_dir: Some(home_dir.clone()), ..Default::default() };
+
+    assert_refused(&guard, &home_dir, "it is the home directory");
+    assert_refused(&guard, dir.path(), "it contains the home directory");
+    assert_eq!(check(&guard, &home_dir.join("project")), None);
+}
+
+#[test]
+fn scan_roots_are_refused_but_not_their_content() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir(root.path().join("node_modules")).unwrap();
+    let guard = DeletionGuard { roots: vec![root.path().to_path_buf()], ..Default::default() };
+
+    assert_refused(&guard, root.path(), "scanned root");
+    assert_eq!(check(&guard, &root.path().join("node_modules")), None);
+}
+
+#[test]
+fn denylist_ancestors_and_descendants_are_refused() {
+    let root = tempfile::tempdir().unwrap();
+    let denied = root.path().join("projects").join("important");
+    fs::create_dir_all(denied.join("node_modules")).unwrap();
+    fs::create_dir(root.path().join("projects").join("other")).unwrap();
+    let guard = DeletionGuard { denylist: vec![denied.clone()], ..Default::default() };
+
+    assert_refused(&guard, &denied, "is in the denylist");
+    assert_refused(&guard, &denied.join("node_modules"), "is in the denylist");
+    assert_refused(&guard, &root.path().join("projects"), "of the denylist");
+    assert_eq!(check(&guard, &root.path().join("projects").join("other")), None);
+}
+
+#[test]
+fn symlinks_into_the_denylist_are_refused() {
+    let root = tempfile::tempdir().unwrap();
+    let denied = root.path().join("important");
+    fs::create_dir_all(denied.join("node_modules")).unwrap();
+    symlink(denied.join("node_modules"), root.path().join("link")).unwrap();
+    let guard = DeletionGuard { denylist: vec![denied], ..Default::default() };
+
+    assert_refused(&guard, &root.path().join("link"), "is in the denylist");
+}
+
+#[test]
+fn git_directories_are_refused_unless_allowed() {
+    let root = tempfile::tempdir().unwrap();
+    let project = root.path().join("project");
+    fs::create_dir_all(project.join("src").join("vendored").join(".git")).unwrap();
+    fs::create_dir_all(project.join("target")).unwrap();
+    let guard = DeletionGuard::default();
+
+    assert_refused(&guard, &project.join("src").join("vendored").join(".git"), "it is a git directory");
+    assert_refused(&guard, &project, "it contains the git directory");
+    assert_eq!(check(&guard, &project.join("target")), None);
+
+    let guard = DeletionGuard { allow_git: true, ..Default::default() };
+    assert_eq!(check(&guard, &project), None);
+    assert_eq!(check(&guard, &project.join("src").join("vendored").join(".git")), None);
+}
+
+#[test]
+fn git_directories_behind_links_are_not_searched() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("repository").join(".git")).unwrap();
+    fs::create_dir(root.path().join("node_modules")).unwrap();
+    symlink(root.path().join("repository"), root.path().join("node_modules").join("linked")).unwrap();
+
+    // deleting the directory removes the link, not the repository it points to
+    assert_eq!(check(&DeletionGuard::default(), &root.path().join("node_modules")), None);
+}
+
+#[test]
+fn refused_deletion_keeps_the_selection() {
+    let root = tempfile::tempdir().unwrap();
+    let state_dir = tempfile::tempdir().unwrap();
+    let denied = root.path().join("important");
+    fs::create_dir_all(denied.join("node_modules")).unwrap();
+    fs::create_dir_all(root.path().join("other").join("node_modules")).unwrap();
+    let mut config = Config::default();
+    config.root_path.value = vec![root.path().to_path_buf()];
+    config.deletion_strategy.value = DeletionStrategy::Permanent;
+    let layer = ConfigLayer { state_dir: Some(state_dir.path().to_path_buf()), denylist: Some(vec![denied.clone()]), ..Default::default() };
+    config.apply(layer, &ConfigSource::Cli).unwrap();
+    let mut app = App::new_from_config(&config).unwrap();
+    let delete = |app: &mut App| {
+        for _ in 0..2 {
+            app.key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+        }
+    };
+
+    let state = app.state_mut();
+    state.selected_entries = state.entries_path.iter().cloned().collect();
+    delete(&mut app);
+
+    assert_eq!(app.state().entries.items.len(), 2);
+    assert_eq!(app.state().selected_entries.len(), 2);
+
+    let denied_path = denied.canonicalize().unwrap().join("node_modules");
+    app.state_mut().selected_entries.remove(&denied_path);
+    delete(&mut app);
+
+    assert!(!root.path().join("other").join("node_modules").exists());
+    assert!(app.state().selected_entries.is_empty());
+}
@@ -0,0 +1,115 @@
+//! Names which are not valid UTF-8 must be listed, matched and deleted like any other
+#![cfg(unix)]
+
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+use wiper::app::App;
+use wiper::config::{Config, ConfigLayer, ConfigSource};
+use wiper::utils::quarantine;
+use wiper::utils::walker::{DeletionStrategy, delete_entries};
+
+/// `café` encoded in Latin-1
+const LATIN1_NAME: &[u8] = b"caf\xe9";
+
+fn latin1_path(parent: &Path) -> PathBuf {
+    parent.join(OsStr::from_bytes(LATIN1_NAME))
+}
+
+/// App scanning `root_path`, its state being kept in the returned directory,
+/// out of both the user state and the scanned tree
+fn app(root_path: &Path, regex_filter: &str) -> (App, TempDir) {
+    let state_dir = tempfile::tempdir().unwrap();
+    let mut config = Config::default();
+    config.root_path.value = vec![root_path.to_path_buf()];
+    config.regex_filter.value = regex_filter.to_string();
+    config.apply(ConfigLayer { state_dir: Some(state_dir.path().to_path_buf()), ..Default::default() }, &ConfigSource::Cli).unwrap();
+    (App::new_from_config(&config).unwrap(), state_dir)
+}
+
+fn listed_paths(app: &App) -> Vec<PathBuf> {
+    app.state().entries.items.iter().map(|entry| entry.path().to_path_buf()).collect()
+}
+
+#[test]
+fn scan_skips_non_utf8_names_not_matching() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(latin1_path(root.path()).join("node_modules")).unwrap();
+    fs::write(latin1_path(root.path()).join(OsStr::from_bytes(b"\xff\xfe")), "content").unwrap();
+
+    let (app, _state_dir) = app(root.path(), "^node_modules$");
+
+    assert_eq!(listed_paths(&app), vec![latin1_path(root.path()).join("node_modules")]);
+}
+
+#[test]
+fn filter_matches_raw_bytes() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir(latin1_path(root.path())).unwrap();
+    fs::create_dir(root.path().join("café")).unwrap();
+
+    let (app, _state_dir) = app(root.path(), r"^(?-u:caf\xE9)$");
+
+    assert_eq!(listed_paths(&app), vec![latin1_path(root.path())]);
+}
+
+#[test]
+fn root_path_may_not_be_utf8() {
+    let parent = tempfile::tempdir().unwrap();
+    let root = latin1_path(parent.path());
+    fs::create_dir_all(root.join("node_modules")).unwrap();
+
+    let (app, _state_dir) = app(&root, "^node_modules$");
+
+    assert_eq!(listed_paths(&app), vec![root.join("node_modules")]);
+}
+
+#[test]
+fn selection_pattern_matches_raw_bytes() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(latin1_path(root.path()).join("node_modules")).unwrap();
+    fs::create_dir_all(root.path().join("other").join("node_modules")).unwrap();
+
+    let (mut app, _state_dir) = app(root.path(), "^node_modules$");
+    app.select_matching(r"(?-u:\xE9)/node_modules$");
+
+    let state = app.state();
+    let selected_paths = state.selected_indices().map(|idx| state.entries.items[idx].path().to_path_buf()).collect::<Vec<_>>();
+    assert_eq!(selected_paths, vec![latin1_path(root.path()).join("node_modules")]);
+}
+
+#[test]
+fn permanent_deletion_removes_non_utf8_entries() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(latin1_path(root.path()).join("nested")).unwrap();
+    fs::write(latin1_path(root.path()).join("nested").join(OsStr::from_bytes(b"\xe9t\xe9")), "content").unwrap();
+
+    let (app, _state_dir) = app(root.path(), r"^(?-u:caf\xE9)$");
+    let state = app.state();
+    let targets = (0..state.entries.items.len()).map(|idx| state.deletion_target(idx)).collect::<Vec<_>>();
+    let report = delete_entries(&targets, DeletionStrategy::Permanent, &state.deletion_settings);
+
+    assert!(report.error.is_none(), "{:?}", report.error);
+    assert_eq!(report.deleted, vec![latin1_path(&root.path().canonicalize().unwrap())]);
+    assert!(!latin1_path(root.path()).exists());
+    assert!(root.path().exists());
+}
+
+#[test]
+fn quarantine_restores_non_utf8_entries() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = tempfile::tempdir().unwrap();
+    let original_path = latin1_path(&root.path().canonicalize().unwrap());
+    fs::create_dir_all(original_path.join("nested")).unwrap();
+    fs::write(original_path.join("nested").join(OsStr::from_bytes(b"\xe9t\xe9")), "content").unwrap();
+
+    let id = quarantine::quarantine(&original_path, 1, 7, quarantine_dir.path()).unwrap();
+    assert!(!original_path.exists());
+    assert_eq!(quarantine::list(quarantine_dir.path()).unwrap()[0].manifest.original_path, original_path);
+
+    assert_eq!(quarantine::restore(quarantine_dir.path(), &id).unwrap(), original_path);
+    assert!(original_path.join("nested").join(OsStr::from_bytes(b"\xe9t\xe9")).exists());
+}
@@ -0,0 +1,234 @@
+//! Quarantined entries must come back exactly as they were, and only expired ones be purged
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use wiper::app::App;
+use wiper::config::{Config, ConfigLayer, ConfigSource};
+use wiper::utils::guard::{DeletionGuard, mount_points};
+use wiper::utils::quarantine::{self, copy_recursive, parse_age};
+use wiper::utils::walker::{count_and_size, delete_entries, measure_content, DeletionSettings, DeletionStrategy, DeletionTarget};
+
+fn write_tree(dir: &Path) {
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("top.txt"), "top").unwrap();
+    fs::write(dir.join("nested").join("deep.txt"), "deep").unwrap();
+}
+
+/// Quarantine `path` with its content measured as the scan does
+fn quarantine_measured(path: &Path, quarantine_dir: &Path) -> std::io::Result<String> {
+    let (file_count, size) = count_and_size(path);
+    quarantine::quarantine(path, file_count, size, quarantine_dir)
+}
+
+#[test]
+fn copy_recursive_copies_a_single_file() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("file"), "content").unwrap();
+    fs::create_dir(dir.path().join("slot")).unwrap();
+
+    copy_recursive(&dir.path().join("file"), &dir.path().join("slot").join("content")).unwrap();
+
+    assert_eq!(fs::read_to_string(dir.path().join("slot").join("content")).unwrap(), "content");
+}
+
+#[test]
+fn copy_recursive_copies_a_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tree(&dir.path().join("tree"));
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("top.txt", dir.path().join("tree").join("link")).unwrap();
+
+    copy_recursive(&dir.path().join("tree"), &dir.path().join("copy")).unwrap();
+
+    assert_eq!(fs::read_to_string(dir.path().join("copy").join("top.txt")).unwrap(), "top");
+    assert_eq!(fs::read_to_string(dir.path().join("copy").join("nested").join("deep.txt")).unwrap(), "deep");
+    #[cfg(unix)]
+    assert_eq!(fs::read_link(dir.path().join("copy").join("link")).unwrap(), Path::new("top.txt"));
+}
+
+#[test]
+fn quarantine_then_restore_a_directory() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = tempfile::tempdir().unwrap();
+    let original_path = root.path().canonicalize().unwrap().join("node_modules");
+    write_tree(&original_path);
+
+    let id = quarantine_measured(&original_path, quarantine_dir.path()).unwrap();
+
+    assert!(!original_path.exists());
+    let entries = quarantine::list(quarantine_dir.path()).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].id, id);
+    assert_eq!(entries[0].manifest.original_path, original_path);
+    assert_eq!(entries[0].manifest.file_count, 2);
+    assert_eq!(entries[0].manifest.size, 7);
+
+    assert_eq!(quarantine::restore(quarantine_dir.path(), &id).unwrap(), original_path);
+
+    assert_eq!(fs::read_to_string(original_path.join("nested").join("deep.txt")).unwrap(), "deep");
+    assert!(quarantine::list(quarantine_dir.path()).unwrap().is_empty());
+}
+
+#[test]
+fn quarantine_then_restore_a_file() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = tempfile::tempdir().unwrap();
+    let original_path = root.path().join("build.log");
+    fs::write(&original_path, "log").unwrap();
+
+    let id = quarantine_measured(&original_path, quarantine_dir.path()).unwrap();
+    assert!(!original_path.exists());
+
+    quarantine::restore(quarantine_dir.path(), &id).unwrap();
+    assert_eq!(fs::read_to_string(&original_path).unwrap(), "log");
+}
+
+#[cfg(unix)]
+#[test]
+fn quarantine_then_restore_a_link() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = tempfile::tempdir().unwrap();
+    let shared = root.path().join("shared");
+    write_tree(&shared);
+    let link = root.path().canonicalize().unwrap().join("node_modules");
+    std::os::unix::fs::symlink(&shared, &link).unwrap();
+
+    let id = quarantine_measured(&link, quarantine_dir.path()).unwrap();
+
+    assert!(link.symlink_metadata().is_err());
+    assert_eq!(fs::read_to_string(shared.join("top.txt")).unwrap(), "top");
+    assert_eq!(quarantine::list(quarantine_dir.path()).unwrap()[0].manifest.original_path, link);
+
+    quarantine::restore(quarantine_dir.path(), &id).unwrap();
+    assert_eq!(fs::read_link(&link).unwrap(), shared);
+}
+
+#[cfg(unix)]
+#[test]
+fn copy_recursive_copies_a_link_as_a_link() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tree(&dir.path().join("tree"));
+    std::os::unix::fs::symlink(dir.path().join("tree"), dir.path().join("link")).unwrap();
+
+    copy_recursive(&dir.path().join("link"), &dir.path().join("copy")).unwrap();
+
+    assert_eq!(fs::read_link(dir.path().join("copy")).unwrap(), dir.path().join("tree"));
+}
+
+#[cfg(unix)]
+#[test]
+fn deleting_a_scanned_link_records_the_link() {
+    let root = tempfile::tempdir().unwrap();
+    let state_dir = tempfile::tempdir().unwrap();
+    let shared = root.path().join("shared");
+    write_tree(&shared);
+    let link = root.path().join("node_modules");
+    std::os::unix::fs::symlink(&shared, &link).unwrap();
+    // the scan follows links
+    let entry = walkdir::WalkDir::new(&link).follow_links(true).into_iter().next().unwrap().unwrap();
+    let settings = DeletionSettings {
+        quarantine_dir: state_dir.path().join("quarantine"),
+        audit_log: state_dir.path().join("audit.jsonl"),
+        ..Default::default()
+    };
+
+    let report = delete_entries(&[DeletionTarget::new(entry, measure_content(&link))], DeletionStrategy::Quarantine, &settings);
+
+    assert!(report.error.is_none(), "{:?}", report.error);
+    assert_eq!(report.deleted, vec![root.path().canonicalize().unwrap().join("node_modules")]);
+    assert!(link.symlink_metadata().is_err());
+    assert_eq!(fs::read_to_string(shared.join("nested").join("deep.txt")).unwrap(), "deep");
+}
+
+#[test]
+fn scan_skips_the_quarantine() {
+    let root = tempfile::tempdir().unwrap();
+    let state_dir = root.path().join(".local").join("state").join("wiper");
+    let project = root.path().join("project").join("node_modules");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(state_dir.join("quarantine")).unwrap();
+    // the quarantine then holds `<id>/content/node_modules`
+    quarantine_measured(project.parent().unwrap(), &state_dir.join("quarantine")).unwrap();
+    fs::create_dir_all(&project).unwrap();
+
+    let mut config = Config::default();
+    config.root_path.value = vec![root.path().to_path_buf()];
+    config.apply(ConfigLayer { state_dir: Some(state_dir.clone()), ..Default::default() }, &ConfigSource::Cli).unwrap();
+    let app = App::new_from_config(&config).unwrap();
+
+    let listed_paths = app.state().entries.items.iter().map(|entry| entry.path().to_path_buf()).collect::<Vec<_>>();
+    assert_eq!(listed_paths, vec![project]);
+}
+
+#[test]
+fn guard_protects_the_quarantine() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = root.path().join("state").join("quarantine");
+    fs::create_dir_all(quarantine_dir.join("20000101-000000-0")).unwrap();
+    let guard = DeletionGuard { quarantine_dir: Some(quarantine_dir.clone()), ..Default::default() };
+
+    let check = |path: &Path| guard.check(path, None, &mount_points());
+
+    assert!(check(&quarantine_dir.join("20000101-000000-0")).is_some_and(|reason| reason.contains("is in the quarantine")));
+    assert!(check(&root.path().join("state")).is_some_and(|reason| reason.contains("contains the quarantine")));
+    assert_eq!(check(&root.path().join("other")), None);
+}
+
+#[test]
+fn restore_does_not_overwrite_the_original_path() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = tempfile::tempdir().unwrap();
+    let original_path = root.path().join("build.log");
+    fs::write(&original_path, "quarantined").unwrap();
+    let id = quarantine_measured(&original_path, quarantine_dir.path()).unwrap();
+    fs::write(&original_path, "recreated").unwrap();
+
+    let error = quarantine::restore(quarantine_dir.path(), &id).unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(fs::read_to_string(&original_path).unwrap(), "recreated");
+    assert_eq!(quarantine::list(quarantine_dir.path()).unwrap().len(), 1);
+}
+
+#[test]
+fn restore_an_unknown_id_fails() {
+    let quarantine_dir = tempfile::tempdir().unwrap();
+
+    let error = quarantine::restore(quarantine_dir.path(), "20000101-000000-0").unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn purge_removes_only_expired_entries() {
+    let root = tempfile::tempdir().unwrap();
+    let quarantine_dir = tempfile::tempdir().unwrap();
+    fs::write(root.path().join("build.log"), "log").unwrap();
+    quarantine_measured(&root.path().join("build.log"), quarantine_dir.path()).unwrap();
+
+    assert!(quarantine::purge(quarantine_dir.path(), chrono::Duration::days(1), false).unwrap().is_empty());
+    assert_eq!(quarantine::purge(quarantine_dir.path(), chrono::Duration::zero(), true).unwrap().len(), 1);
+    assert_eq!(quarantine::list(quarantine_dir.path()).unwrap().len(), 1);
+
+    assert_eq!(quarantine::purge(quarantine_dir.path(), chrono::Duration::zero(), false).unwrap().len(), 1);
+    assert!(quarantine::list(quarantine_dir.path()).unwrap().is_empty());
+    assert_eq!(fs::read_dir(quarantine_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn parse_age_units() {
+    assert_eq!(parse_age("30s"), Ok(chrono::Duration::seconds(30)));
+    assert_eq!(parse_age("30m"), Ok(chrono::Duration::minutes(30)));
+    assert_eq!(parse_age("12h"), Ok(chrono::Duration::hours(12)));
+    assert_eq!(parse_age("7d"), Ok(chrono::Duration::days(7)));
+    assert_eq!(parse_age("2w"), Ok(chrono::Duration::weeks(2)));
+}
+
+#[test]
+fn parse_age_rejects_invalid_ages() {
+    for age in ["", "d", "7", "7y", "-7d", "7 d"] {
+        assert!(parse_age(age).is_err(), "{} should be rejected", age);
+    }
+}
@@ -0,0 +1,26 @@
+//! A termination signal must stop the walks, which the main loop only checks between frames
+#![cfg(unix)]
+
+use std::fs;
+
+use signal_hook::consts::SIGTERM;
+use wiper::utils::signals::{Signal, Signals, is_terminating};
+use wiper::utils::walker::{get_dir_list_from_path, measure_content};
+
+#[test]
+fn termination_stops_the_walks() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("project").join("node_modules")).unwrap();
+    fs::write(root.path().join("project").join("file"), "content").unwrap();
+    let is_node_modules = |name: &std::ffi::OsStr| name == "node_modules";
+    assert_eq!(measure_content(root.path()).file_count, 1);
+    assert_eq!(get_dir_list_from_path(&root.path(), &is_node_modules, false, &[]).count(), 1);
+
+    let signals = Signals::register().unwrap();
+    signal_hook::low_level::raise(SIGTERM).unwrap();
+
+    assert!(is_terminating());
+    assert_eq!(signals.take(), Some(Signal::Terminate(SIGTERM)));
+    assert_eq!(measure_content(root.path()).file_count, 0);
+    assert_eq!(get_dir_list_from_path(&root.path(), &is_node_modules, false, &[]).count(), 0);
+}